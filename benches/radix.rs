@@ -0,0 +1,63 @@
+/*
+Copyright ⓒ 2015 grabbag contributors.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+/*!
+Compares `sorted_radix` with the comparison sorts.
+
+Run with `cargo bench --bench radix`.  Each case is run several times, and the median time is reported.
+*/
+extern crate grabbag;
+
+use std::time::{Duration, Instant};
+use grabbag::iter::SortedIterator;
+
+const RUNS: usize = 7;
+
+fn xorshift(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+fn median<F>(mut f: F) -> Duration where F: FnMut() -> usize {
+    let mut times = (0..RUNS).map(|_| {
+        let start = Instant::now();
+        let check = f();
+        let elapsed = start.elapsed();
+        assert!(check > 0);
+        elapsed
+    }).collect::<Vec<_>>();
+    times.sort();
+    times[RUNS / 2]
+}
+
+fn bench<E>(name: &str, v: &[E]) where E: grabbag::iter::radix::RadixKey + Ord + Clone {
+    let sorted = median(|| v.iter().copied().sorted().count());
+    let unstable = median(|| v.iter().copied().sorted_unstable().count());
+    let radix = median(|| v.iter().copied().sorted_radix().count());
+    let ms = |d: Duration| d.as_secs_f64() * 1e3;
+    println!("{:<12} {:>9} {:>12.3} {:>16.3} {:>12.3} {:>8.2}x {:>8.2}x",
+        name, v.len(), ms(sorted), ms(unstable), ms(radix),
+        ms(sorted) / ms(radix), ms(unstable) / ms(radix));
+}
+
+fn main() {
+    println!("{:<12} {:>9} {:>12} {:>16} {:>12} {:>9} {:>9}",
+        "key", "n", "sorted (ms)", "unstable (ms)", "radix (ms)", "vs sorted", "vs unstbl");
+    let mut state = 0x2545_f491_4f6c_dd1d;
+    for &n in &[10_000, 1_000_000] {
+        let v: Vec<u32> = (0..n).map(|_| xorshift(&mut state) as u32).collect();
+        bench("u32", &v);
+        let v: Vec<u64> = (0..n).map(|_| xorshift(&mut state)).collect();
+        bench("u64", &v);
+        let v: Vec<i64> = (0..n).map(|_| xorshift(&mut state) as i64).collect();
+        bench("i64", &v);
+    }
+}
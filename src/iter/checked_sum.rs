@@ -0,0 +1,164 @@
+/*
+Copyright ⓒ 2015 grabbag contributors.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+use std::error::Error;
+use std::fmt;
+
+/**
+Sums and products of integer sequences with explicit overflow behaviour.
+*/
+pub trait CheckedSumIterator<E>: Iterator<Item=E> + Sized where E: CheckedSumInt {
+    /**
+Sums the elements of the iterator, stopping at the first overflow.
+
+An empty iterator sums to zero.  If the running total overflows, the error contains the index of the element which caused it.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::CheckedSumIterator;
+# fn main() {
+let v = vec![200u8, 50, 6, 1];
+let r = v.into_iter().checked_sum();
+assert_eq!(r.unwrap_err().index, 2);
+# }
+```
+    */
+    fn checked_sum(self) -> Result<E, Overflow> {
+        let mut total = E::zero();
+        for (i, e) in self.enumerate() {
+            total = match total.checked_add(e) {
+                Some(total) => total,
+                None => return Err(Overflow { index: i })
+            };
+        }
+        Ok(total)
+    }
+
+    /**
+Multiplies the elements of the iterator together, stopping at the first overflow.
+
+An empty iterator has a product of one.  If the running product overflows, the error contains the index of the element which caused it.
+    */
+    fn checked_product(self) -> Result<E, Overflow> {
+        let mut total = E::one();
+        for (i, e) in self.enumerate() {
+            total = match total.checked_mul(e) {
+                Some(total) => total,
+                None => return Err(Overflow { index: i })
+            };
+        }
+        Ok(total)
+    }
+
+    /**
+Sums the elements of the iterator, clamping the running total to the bounds of the element type at each step.
+
+Note that, for signed types, a clamped total can be brought back into range by subsequent negative elements.
+    */
+    fn saturating_sum(self) -> E {
+        self.fold(E::zero(), |a, b| a.saturating_add(b))
+    }
+
+    /**
+Sums the elements of the iterator, wrapping around at the bounds of the element type.
+    */
+    fn wrapping_sum(self) -> E {
+        self.fold(E::zero(), |a, b| a.wrapping_add(b))
+    }
+}
+
+impl<It, E> CheckedSumIterator<E> for It where It: Iterator<Item=E>, E: CheckedSumInt {}
+
+/**
+The error returned when a checked sum or product overflows.
+*/
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Overflow {
+    /**
+The index of the element which caused the overflow.
+    */
+    pub index: usize,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "arithmetic overflow at element {}", self.index)
+    }
+}
+
+impl Error for Overflow {}
+
+/**
+Primitive integer types which can be used with `CheckedSumIterator`.
+*/
+pub trait CheckedSumInt: Copy {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn saturating_add(self, other: Self) -> Self;
+    fn wrapping_add(self, other: Self) -> Self;
+}
+
+macro_rules! impl_checked_sum_int {
+    ($($ty:ty),*) => {
+        $(
+            impl CheckedSumInt for $ty {
+                fn zero() -> $ty { 0 }
+                fn one() -> $ty { 1 }
+                fn checked_add(self, other: $ty) -> Option<$ty> { <$ty>::checked_add(self, other) }
+                fn checked_mul(self, other: $ty) -> Option<$ty> { <$ty>::checked_mul(self, other) }
+                fn saturating_add(self, other: $ty) -> $ty { <$ty>::saturating_add(self, other) }
+                fn wrapping_add(self, other: $ty) -> $ty { <$ty>::wrapping_add(self, other) }
+            }
+        )*
+    };
+}
+
+impl_checked_sum_int! { i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize }
+
+#[test]
+fn test_checked_sum() {
+    let v: Vec<u32> = vec![];
+    assert_eq!(v.into_iter().checked_sum(), Ok(0));
+
+    let v = vec![1u32, 2, 3, 4];
+    assert_eq!(v.into_iter().checked_sum(), Ok(10));
+
+    let v = vec![1u32, u32::MAX - 2, 1, 1, 1];
+    assert_eq!(v.into_iter().checked_sum(), Err(Overflow { index: 3 }));
+
+    let v = vec![-100i8, -28, -1];
+    assert_eq!(v.into_iter().checked_sum(), Err(Overflow { index: 2 }));
+}
+
+#[test]
+fn test_checked_product() {
+    let v: Vec<i16> = vec![];
+    assert_eq!(v.into_iter().checked_product(), Ok(1));
+
+    let v = vec![2i16, -3, 4];
+    assert_eq!(v.into_iter().checked_product(), Ok(-24));
+
+    let v = vec![16u8, 15, 2, 0];
+    assert_eq!(v.into_iter().checked_product(), Err(Overflow { index: 2 }));
+}
+
+#[test]
+fn test_saturating_wrapping_sum() {
+    let v = vec![100i8, 100, -50];
+    assert_eq!(v.clone().into_iter().saturating_sum(), 77);
+    assert_eq!(v.into_iter().wrapping_sum(), -106);
+
+    let v = vec![250u8, 10];
+    assert_eq!(v.clone().into_iter().saturating_sum(), 255);
+    assert_eq!(v.into_iter().wrapping_sum(), 4);
+}
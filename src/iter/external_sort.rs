@@ -0,0 +1,539 @@
+/*
+Copyright ⓒ 2015 grabbag contributors.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+use std::borrow::Borrow;
+use std::cmp::{min, Ordering};
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::marker::PhantomData;
+use std::mem::{replace, size_of};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::vec;
+use super::merge::{Compare, KMerge, NaturalOrder};
+
+/**
+Sorting of sequences which are too large to fit in memory.
+
+Elements are collected into runs which fit within a memory budget.  Each run is sorted and written to a temporary file.  The sorted runs are then merged back together as the result is iterated.  If there are too many runs to merge at once, they are first merged in several passes into fewer, longer runs.
+
+Temporary files are deleted as soon as they have been fully read, or when the resulting iterator is dropped.
+*/
+pub trait ExternalSortIterator<E>: Iterator<Item=E> + Sized where E: ExternalSortable {
+    /**
+Sorts the elements of the input iterator, using temporary files as described by `config`.
+
+Sorting is stable.  Errors encountered whilst writing runs, or whilst merging them in intermediate passes, are returned immediately; errors encountered whilst reading runs back are yielded from the resulting iterator, after which it stops.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::ExternalSortIterator;
+# use grabbag::iter::external_sort::ExternalSortConfig;
+# fn main() {
+let config = ExternalSortConfig::new(1024);
+let r = (0..10000u32).rev().external_sort(config).unwrap();
+assert!(r.map(|e| e.unwrap()).eq(0..10000));
+# }
+```
+    */
+    fn external_sort(self, config: ExternalSortConfig) -> io::Result<ExternalSorted<E, NaturalOrder>> where E: Ord {
+        ExternalSorted::new(self, config, NaturalOrder)
+    }
+
+    /**
+Sorts the elements of the input iterator according to `compare`, using temporary files as described by `config`.
+    */
+    fn external_sort_by<F>(self, config: ExternalSortConfig, compare: F) -> io::Result<ExternalSorted<E, F>> where F: FnMut(&E, &E) -> Ordering {
+        ExternalSorted::new(self, config, compare)
+    }
+}
+
+impl<It, E> ExternalSortIterator<E> for It where It: Iterator<Item=E>, E: ExternalSortable {}
+
+/**
+Elements which can be written to and read back from temporary files.
+*/
+pub trait ExternalSortable: Sized {
+    /**
+Writes this element to `w`.
+    */
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()>;
+
+    /**
+Reads an element previously written by `write_to`.
+    */
+    fn read_from<R: Read>(r: &mut R) -> io::Result<Self>;
+
+    /**
+Returns the number of bytes this element owns on the heap, for the purposes of the memory budget.
+
+The default implementation returns zero.
+    */
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+macro_rules! impl_external_sortable_int {
+    ($($ty:ty),*) => {
+        $(
+            impl ExternalSortable for $ty {
+                fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+                    w.write_all(&self.to_le_bytes())
+                }
+
+                fn read_from<R: Read>(r: &mut R) -> io::Result<$ty> {
+                    let mut buf = [0; size_of::<$ty>()];
+                    r.read_exact(&mut buf)?;
+                    Ok(<$ty>::from_le_bytes(buf))
+                }
+            }
+        )*
+    };
+}
+
+impl_external_sortable_int! { i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize }
+
+impl ExternalSortable for Vec<u8> {
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        (self.len() as u64).write_to(w)?;
+        w.write_all(self)
+    }
+
+    fn read_from<R: Read>(r: &mut R) -> io::Result<Vec<u8>> {
+        let len = u64::read_from(r)?;
+        let mut buf = vec![];
+        r.take(len).read_to_end(&mut buf)?;
+        if buf.len() as u64 != len {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated element in sort run"));
+        }
+        Ok(buf)
+    }
+
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl ExternalSortable for String {
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        (self.len() as u64).write_to(w)?;
+        w.write_all(self.as_bytes())
+    }
+
+    fn read_from<R: Read>(r: &mut R) -> io::Result<String> {
+        String::from_utf8(Vec::<u8>::read_from(r)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<A, B> ExternalSortable for (A, B) where A: ExternalSortable, B: ExternalSortable {
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.0.write_to(w)?;
+        self.1.write_to(w)
+    }
+
+    fn read_from<R: Read>(r: &mut R) -> io::Result<(A, B)> {
+        let a = A::read_from(r)?;
+        let b = B::read_from(r)?;
+        Ok((a, b))
+    }
+
+    fn heap_size(&self) -> usize {
+        self.0.heap_size() + self.1.heap_size()
+    }
+}
+
+/**
+Configuration for `ExternalSortIterator`.
+*/
+#[derive(Clone, Debug)]
+pub struct ExternalSortConfig {
+    memory_budget: usize,
+    temp_dir: Option<PathBuf>,
+    fan_in: usize,
+}
+
+/*
+The default maximum number of runs merged at once.
+*/
+const DEFAULT_FAN_IN: usize = 16;
+
+/*
+The largest buffer used for reading or writing a single run, regardless of the budget.
+*/
+const MAX_IO_BUFFER: usize = 64 * 1024;
+
+impl ExternalSortConfig {
+    /**
+Creates a configuration which keeps at most `memory_budget` bytes of elements in memory at once.
+
+The size of an element is taken to be its `size_of` plus its `ExternalSortable::heap_size`.  Part of the budget is set aside for the buffers used to read and write temporary files, which leaves less room for elements.  Each run holds at least one element, regardless of the budget, and the merge holds one element from each run being merged.
+    */
+    pub fn new(memory_budget: usize) -> ExternalSortConfig {
+        ExternalSortConfig {
+            memory_budget,
+            temp_dir: None,
+            fan_in: DEFAULT_FAN_IN,
+        }
+    }
+
+    /**
+Sets the directory in which temporary files are created.  This defaults to `std::env::temp_dir()`.
+    */
+    pub fn temp_dir<P: Into<PathBuf>>(mut self, dir: P) -> ExternalSortConfig {
+        self.temp_dir = Some(dir.into());
+        self
+    }
+
+    /**
+Sets the maximum number of runs which are merged at once.  This defaults to 16.
+
+At most this many temporary files are open for reading at once, plus one for writing.  If there are more runs than this, they are merged in several passes, which means reading and writing every element more than once.
+
+# Failure
+
+Panics if `fan_in` is less than two.
+    */
+    pub fn fan_in(mut self, fan_in: usize) -> ExternalSortConfig {
+        assert!(fan_in >= 2, "external sort fan-in must be at least 2, got {}", fan_in);
+        self.fan_in = fan_in;
+        self
+    }
+
+    /*
+Splits the memory budget, returning the size of each file buffer and the budget left for each run.
+
+The most memory is needed during an intermediate merge, when `fan_in` runs are being read and one written, whilst the final run is still held in memory.
+    */
+    fn split_budget(&self) -> (usize, usize) {
+        let buffers = self.fan_in + 1;
+        let io_buffer = min(self.memory_budget / (2 * buffers), MAX_IO_BUFFER).max(1);
+        (io_buffer, self.memory_budget.saturating_sub(io_buffer * buffers))
+    }
+}
+
+static NEXT_RUN_ID: AtomicUsize = AtomicUsize::new(0);
+
+/*
+A sorted run which has been written to a temporary file.  The file is not opened for reading until the first element is needed, and is deleted when the run is exhausted or dropped.
+*/
+#[derive(Debug)]
+struct RunFile<E> {
+    path: PathBuf,
+    reader: Option<BufReader<File>>,
+    io_buffer: usize,
+    remaining: usize,
+    closed: bool,
+    _marker: PhantomData<E>,
+}
+
+impl<E> RunFile<E> where E: ExternalSortable {
+    fn write<I, B>(dir: &Path, io_buffer: usize, run: I) -> io::Result<RunFile<E>> where I: IntoIterator<Item=io::Result<B>>, B: Borrow<E> {
+        let id = NEXT_RUN_ID.fetch_add(1, AtomicOrdering::SeqCst);
+        let path = dir.join(format!("grabbag-sort-{}-{}.tmp", process::id(), id));
+        let file = OpenOptions::new().write(true).create_new(true).open(&path)?;
+
+        // From here on, dropping `run_file` will clean up after us.
+        let mut run_file = RunFile {
+            path,
+            reader: None,
+            io_buffer,
+            remaining: 0,
+            closed: false,
+            _marker: PhantomData,
+        };
+
+        let mut writer = BufWriter::with_capacity(io_buffer, file);
+        for e in run {
+            e?.borrow().write_to(&mut writer)?;
+            run_file.remaining += 1;
+        }
+        let file = writer.into_inner().map_err(|err| err.into_error())?;
+        drop(file);
+
+        Ok(run_file)
+    }
+}
+
+impl<E> RunFile<E> {
+    fn close(&mut self) {
+        // Close the file before removing it; some platforms refuse to remove open files.
+        self.reader = None;
+        if !self.closed {
+            self.closed = true;
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+impl<E> Drop for RunFile<E> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl<E> Iterator for RunFile<E> where E: ExternalSortable {
+    type Item = io::Result<E>;
+
+    fn next(&mut self) -> Option<io::Result<E>> {
+        if self.remaining == 0 {
+            self.close();
+            return None;
+        }
+        if self.reader.is_none() {
+            match File::open(&self.path) {
+                Ok(file) => self.reader = Some(BufReader::with_capacity(self.io_buffer, file)),
+                Err(err) => {
+                    self.remaining = 0;
+                    return Some(Err(err));
+                }
+            }
+        }
+        let r = match self.reader {
+            Some(ref mut reader) => E::read_from(reader),
+            None => return None
+        };
+        match r {
+            Ok(_) => self.remaining -= 1,
+            Err(_) => self.remaining = 0,
+        }
+        Some(r)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+#[derive(Debug)]
+enum Run<E> {
+    Memory(vec::IntoIter<E>),
+    File(RunFile<E>),
+}
+
+impl<E> Iterator for Run<E> where E: ExternalSortable {
+    type Item = io::Result<E>;
+
+    fn next(&mut self) -> Option<io::Result<E>> {
+        match *self {
+            Run::Memory(ref mut it) => it.next().map(Ok),
+            Run::File(ref mut it) => it.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match *self {
+            Run::Memory(ref it) => it.size_hint(),
+            Run::File(ref it) => it.size_hint(),
+        }
+    }
+}
+
+/*
+Orders read results so that errors come first, and are therefore reported as soon as possible.
+*/
+#[derive(Debug)]
+struct ResultOrder<C>(C);
+
+impl<E, C> Compare<io::Result<E>> for ResultOrder<C> where C: Compare<E> {
+    fn compare(&mut self, a: &io::Result<E>, b: &io::Result<E>) -> Ordering {
+        match (a, b) {
+            (Ok(a), Ok(b)) => self.0.compare(a, b),
+            (Err(_), Err(_)) => Ordering::Equal,
+            (Err(_), _) => Ordering::Less,
+            (_, Err(_)) => Ordering::Greater,
+        }
+    }
+}
+
+/**
+An iterator over the elements of an externally sorted sequence.
+*/
+#[derive(Debug)]
+#[must_use = "iterator adaptors are lazy and do nothing unless consumed"]
+pub struct ExternalSorted<E, C> {
+    merge: KMerge<Run<E>, io::Result<E>, ResultOrder<C>>,
+    failed: bool,
+}
+
+impl<E, C> ExternalSorted<E, C> where E: ExternalSortable, C: Compare<E> {
+    fn new<It>(iter: It, config: ExternalSortConfig, mut compare: C) -> io::Result<ExternalSorted<E, C>> where It: Iterator<Item=E> {
+        let (io_buffer, run_budget) = config.split_budget();
+        let dir = config.temp_dir.unwrap_or_else(env::temp_dir);
+        let mut files = vec![];
+        let mut buffer = vec![];
+        let mut used = 0;
+
+        for e in iter {
+            let size = size_of::<E>() + e.heap_size();
+            if !buffer.is_empty() && used + size > run_budget {
+                buffer.sort_by(|a, b| compare.compare(a, b));
+                files.push(RunFile::write(&dir, io_buffer, buffer.iter().map(Ok))?);
+                buffer.clear();
+                used = 0;
+            }
+            used += size;
+            buffer.push(e);
+        }
+
+        // Merge adjacent runs until the remaining files and the final run can be merged at once.  Only adjacent runs are merged, so that the sort remains stable.
+        while files.len() + 1 > config.fan_in {
+            let mut rest = files;
+            files = vec![];
+            while !rest.is_empty() {
+                let tail = rest.split_off(min(config.fan_in, rest.len()));
+                let mut chunk = replace(&mut rest, tail);
+                files.push(match chunk.len() {
+                    1 => chunk.pop().expect("run"),
+                    _ => {
+                        let merge = KMerge::new(chunk.into_iter(), ResultOrder(|a: &E, b: &E| compare.compare(a, b)));
+                        RunFile::write(&dir, io_buffer, merge)?
+                    }
+                });
+            }
+        }
+
+        // The last run never needs to leave memory.
+        buffer.sort_by(|a, b| compare.compare(a, b));
+        let mut runs = files.into_iter().map(Run::File).collect::<Vec<_>>();
+        runs.push(Run::Memory(buffer.into_iter()));
+
+        Ok(ExternalSorted {
+            merge: KMerge::new(runs.into_iter(), ResultOrder(compare)),
+            failed: false,
+        })
+    }
+}
+
+impl<E, C> Iterator for ExternalSorted<E, C> where E: ExternalSortable, C: Compare<E> {
+    type Item = io::Result<E>;
+
+    fn next(&mut self) -> Option<io::Result<E>> {
+        if self.failed {
+            return None;
+        }
+        let r = self.merge.next();
+        if let Some(Err(_)) = r {
+            self.failed = true;
+        }
+        r
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.failed {
+            true => (0, Some(0)),
+            false => self.merge.size_hint()
+        }
+    }
+}
+
+#[cfg(test)]
+fn temp_files_in(dir: &Path) -> usize {
+    fs::read_dir(dir).unwrap().count()
+}
+
+#[test]
+fn test_external_sort() {
+    let dir = env::temp_dir().join(format!("grabbag-test-external-sort-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    // A simple LCG, so that the input isn't in any particular order.
+    let mut seed = 1u32;
+    let v: Vec<u32> = (0..5000).map(|_| {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        seed >> 16
+    }).collect();
+
+    let config = ExternalSortConfig::new(400).temp_dir(&dir);
+    let mut it = v.clone().into_iter().external_sort(config).unwrap();
+    assert_eq!(it.size_hint(), (5000, Some(5000)));
+    assert!(temp_files_in(&dir) > 1);
+
+    let mut expected = v.clone();
+    expected.sort();
+    let first = it.next().unwrap().unwrap();
+    assert_eq!(first, expected[0]);
+
+    // Dropping the iterator early must remove the remaining files.
+    drop(it);
+    assert_eq!(temp_files_in(&dir), 0);
+
+    let config = ExternalSortConfig::new(400).temp_dir(&dir);
+    let r: Vec<u32> = v.into_iter().external_sort(config).unwrap().map(|e| e.unwrap()).collect();
+    assert_eq!(r, expected);
+    assert_eq!(temp_files_in(&dir), 0);
+
+    // Stability, with a custom ordering.
+    let v: Vec<(u8, String)> = (0..100).map(|i| ((i % 7) as u8, format!("{:03}", i))).collect();
+    let config = ExternalSortConfig::new(500).temp_dir(&dir);
+    let r: Vec<_> = v.clone().into_iter()
+        .external_sort_by(config, |a, b| b.0.cmp(&a.0))
+        .unwrap()
+        .map(|e| e.unwrap())
+        .collect();
+    let mut expected = v;
+    expected.sort_by_key(|e| ::std::cmp::Reverse(e.0));
+    assert_eq!(r, expected);
+    assert_eq!(temp_files_in(&dir), 0);
+
+    fs::remove_dir(&dir).unwrap();
+}
+
+#[test]
+fn test_external_sort_fan_in() {
+    let dir = env::temp_dir().join(format!("grabbag-test-external-sort-fan-in-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    // Stability across several merge passes.
+    let v: Vec<(u8, u32)> = (0..3000).map(|i| ((i * 7919 % 13) as u8, i)).collect();
+    let config = ExternalSortConfig::new(600).temp_dir(&dir).fan_in(3);
+    let it = v.clone().into_iter().external_sort_by(config, |a, b| a.0.cmp(&b.0)).unwrap();
+
+    // Only as many files as the final merge reads at once are left.
+    let files = temp_files_in(&dir);
+    assert!((1..=2).contains(&files), "{} files left", files);
+
+    let r: Vec<_> = it.map(|e| e.unwrap()).collect();
+    let mut expected = v;
+    expected.sort_by_key(|e| e.0);
+    assert_eq!(r, expected);
+    assert_eq!(temp_files_in(&dir), 0);
+
+    fs::remove_dir(&dir).unwrap();
+}
+
+#[test]
+fn test_external_sort_split_budget() {
+    for &budget in &[0, 100, 400, 1 << 20, 1 << 30] {
+        for &fan_in in &[2, 16, 100] {
+            let config = ExternalSortConfig::new(budget).fan_in(fan_in);
+            let (io_buffer, run_budget) = config.split_budget();
+            assert!((1..=MAX_IO_BUFFER).contains(&io_buffer));
+            if budget >= 2 * (fan_in + 1) {
+                assert!(run_budget + io_buffer * (fan_in + 1) <= budget);
+                assert!(run_budget >= budget / 2);
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "external sort fan-in must be at least 2")]
+fn test_external_sort_fan_in_too_small() {
+    let _ = ExternalSortConfig::new(1024).fan_in(1);
+}
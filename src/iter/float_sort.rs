@@ -0,0 +1,255 @@
+/*
+Copyright ⓒ 2015 grabbag contributors.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use super::sorted::{Sorted, SortedIterator};
+
+/**
+Sorting and extrema of floating point sequences.
+
+Unless otherwise noted, these use the IEEE 754 `totalOrder` predicate:
+
+<code>-NaN &lt; -&infin; &lt; ... &lt; -0 &lt; +0 &lt; ... &lt; +&infin; &lt; +NaN</code>
+*/
+pub trait FloatSortIterator<E>: Iterator<Item=E> + Sized where E: TotalOrdFloat {
+    /**
+Returns an iterator over the elements of the input iterator in `totalOrder`.
+
+Note that NaNs with their sign bit set sort before everything else, whilst other NaNs sort after everything else.  Use `sorted_floats_with` to control where NaNs end up.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::FloatSortIterator;
+# fn main() {
+let v = vec![2.5, -1.0, 0.0, -0.0, 1.0];
+let r: Vec<_> = v.into_iter().sorted_floats().collect();
+assert_eq!(r, vec![-1.0, -0.0, 0.0, 1.0, 2.5]);
+# }
+```
+    */
+    fn sorted_floats(self) -> Sorted<E> {
+        self.sorted_by(|a, b| a.total_cmp(b))
+    }
+
+    /**
+Returns an iterator over the elements of the input iterator in `totalOrder`, with NaNs handled according to `nan`.
+
+The sort is stable, so NaNs retain their relative order when they are placed first or last.  This only returns an error when `nan` is `NanPolicy::Error`.
+    */
+    fn sorted_floats_with(self, nan: NanPolicy) -> Result<Sorted<E>, NanError> {
+        match nan {
+            NanPolicy::First => Ok(self.sorted_by(|a, b| match (a.is_nan(), b.is_nan()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                (false, false) => a.total_cmp(b),
+            })),
+            NanPolicy::Last => Ok(self.sorted_by(|a, b| match (a.is_nan(), b.is_nan()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => a.total_cmp(b),
+            })),
+            NanPolicy::Error => {
+                let mut v = vec![];
+                for (i, e) in self.enumerate() {
+                    if e.is_nan() {
+                        return Err(NanError { index: i });
+                    }
+                    v.push(e);
+                }
+                Ok(v.into_iter().sorted_by(|a, b| a.total_cmp(b)))
+            },
+        }
+    }
+
+    /**
+Returns the smallest non-NaN element of the input iterator.
+
+Negative zero is considered smaller than positive zero.  Returns `None` if the iterator is empty or contains only NaNs.
+    */
+    fn min_float(self) -> Option<E> {
+        self.filter(|e| !e.is_nan()).min_by(|a, b| a.total_cmp(b))
+    }
+
+    /**
+Returns the largest non-NaN element of the input iterator.
+
+Positive zero is considered larger than negative zero.  Returns `None` if the iterator is empty or contains only NaNs.
+    */
+    fn max_float(self) -> Option<E> {
+        self.filter(|e| !e.is_nan()).max_by(|a, b| a.total_cmp(b))
+    }
+}
+
+impl<It, E> FloatSortIterator<E> for It where It: Iterator<Item=E>, E: TotalOrdFloat {}
+
+/**
+Where NaNs should be placed when sorting.
+*/
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum NanPolicy {
+    /// Place all NaNs before every other value.
+    First,
+    /// Place all NaNs after every other value.
+    Last,
+    /// Fail if any NaNs are present.
+    Error,
+}
+
+/**
+The error returned when a NaN is found whilst sorting with `NanPolicy::Error`.
+*/
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct NanError {
+    /**
+The index of the first NaN in the input.
+    */
+    pub index: usize,
+}
+
+impl fmt::Display for NanError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "NaN at element {}", self.index)
+    }
+}
+
+impl Error for NanError {}
+
+/**
+Floating point types which can be totally ordered.
+*/
+pub trait TotalOrdFloat: Copy {
+    /**
+Compares two values using the IEEE 754 `totalOrder` predicate.
+    */
+    fn total_cmp(&self, other: &Self) -> Ordering;
+
+    /**
+Returns `true` if this value is a NaN.
+    */
+    fn is_nan(self) -> bool;
+
+    /**
+Returns the raw bit pattern of this value.
+    */
+    fn to_bits_u64(self) -> u64;
+}
+
+impl TotalOrdFloat for f32 {
+    fn total_cmp(&self, other: &f32) -> Ordering { f32::total_cmp(self, other) }
+    fn is_nan(self) -> bool { f32::is_nan(self) }
+    fn to_bits_u64(self) -> u64 { u64::from(self.to_bits()) }
+}
+
+impl TotalOrdFloat for f64 {
+    fn total_cmp(&self, other: &f64) -> Ordering { f64::total_cmp(self, other) }
+    fn is_nan(self) -> bool { f64::is_nan(self) }
+    fn to_bits_u64(self) -> u64 { self.to_bits() }
+}
+
+/**
+A wrapper which orders floating point values using the IEEE 754 `totalOrder` predicate.
+
+This allows floats to be used with anything that requires `Ord`, such as `SortedIterator::sorted` or as a key in a `BTreeMap`.  Equality is consistent with the ordering: `-0.0` and `+0.0` are distinct, and a NaN is equal to any NaN with the same bit pattern.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::SortedIterator;
+# use grabbag::iter::float_sort::OrdFloat;
+# fn main() {
+let v = vec![3.0, 1.0, 2.0];
+let r: Vec<_> = v.into_iter().sorted_by_key(|&e| OrdFloat(e)).collect();
+assert_eq!(r, vec![1.0, 2.0, 3.0]);
+# }
+```
+*/
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OrdFloat<F>(pub F);
+
+impl<F> PartialEq for OrdFloat<F> where F: TotalOrdFloat {
+    fn eq(&self, other: &OrdFloat<F>) -> bool {
+        self.0.total_cmp(&other.0) == Ordering::Equal
+    }
+}
+
+impl<F> Eq for OrdFloat<F> where F: TotalOrdFloat {}
+
+impl<F> PartialOrd for OrdFloat<F> where F: TotalOrdFloat {
+    fn partial_cmp(&self, other: &OrdFloat<F>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F> Ord for OrdFloat<F> where F: TotalOrdFloat {
+    fn cmp(&self, other: &OrdFloat<F>) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl<F> Hash for OrdFloat<F> where F: TotalOrdFloat {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits_u64().hash(state)
+    }
+}
+
+#[test]
+fn test_sorted_floats() {
+    let nan = f64::NAN;
+    let v = vec![1.0, -nan, nan, -1.0, f64::INFINITY, 0.0, -0.0];
+
+    let r: Vec<_> = v.clone().into_iter().sorted_floats().map(|e| e.to_bits()).collect();
+    let expected: Vec<_> = vec![-nan, -1.0, -0.0, 0.0, 1.0, f64::INFINITY, nan]
+        .into_iter().map(|e| e.to_bits()).collect();
+    assert_eq!(r, expected);
+
+    let r: Vec<_> = v.clone().into_iter().sorted_floats_with(NanPolicy::First).unwrap().collect();
+    assert!(r[0].is_nan() && r[1].is_nan());
+    assert_eq!(&r[2..], &[-1.0, -0.0, 0.0, 1.0, f64::INFINITY]);
+
+    let r: Vec<_> = v.clone().into_iter().sorted_floats_with(NanPolicy::Last).unwrap().collect();
+    assert_eq!(&r[..5], &[-1.0, -0.0, 0.0, 1.0, f64::INFINITY]);
+    assert!(r[5].is_nan() && r[6].is_nan());
+
+    let r = v.into_iter().sorted_floats_with(NanPolicy::Error);
+    assert_eq!(r.err(), Some(NanError { index: 1 }));
+
+    let v = vec![2.0f32, 1.0];
+    let r: Vec<_> = v.into_iter().sorted_floats_with(NanPolicy::Error).unwrap().collect();
+    assert_eq!(r, vec![1.0, 2.0]);
+}
+
+#[test]
+fn test_min_max_float() {
+    let v = vec![f32::NAN, 2.0, -3.5, 0.0, f32::NAN];
+    assert_eq!(v.clone().into_iter().min_float(), Some(-3.5));
+    assert_eq!(v.into_iter().max_float(), Some(2.0));
+
+    let v = vec![f64::NAN];
+    assert_eq!(v.clone().into_iter().min_float(), None);
+    assert_eq!(v.into_iter().max_float(), None);
+}
+
+#[test]
+fn test_ord_float() {
+    use std::collections::BTreeSet;
+
+    let s: BTreeSet<_> = vec![1.0, -0.0, 0.0, 1.0, f64::NAN].into_iter().map(OrdFloat).collect();
+    assert_eq!(s.len(), 4);
+    assert!(OrdFloat(-0.0f64) < OrdFloat(0.0));
+    assert!(OrdFloat(f64::INFINITY) < OrdFloat(f64::NAN));
+    assert_eq!(OrdFloat(f64::NAN), OrdFloat(f64::NAN));
+}
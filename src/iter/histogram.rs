@@ -0,0 +1,391 @@
+/*
+Copyright ⓒ 2015 grabbag contributors.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+use std::cmp::min;
+
+/**
+Sorts the elements of a sequence into bins.
+
+Bins are specified using `Bins`, which can be constructed from either a `(min, max, n)` tuple (for `n` bins of equal width), or a sorted `Vec<f64>` of bin edges.  Each bin includes its lower edge, but not its upper edge; the exception is the last bin, which includes both.
+
+Elements can be of any primitive numeric type, or a reference to one; see `AsF64`.
+*/
+pub trait HistogramIterator<E>: Iterator<Item=E> + Sized {
+    /**
+Counts the number of elements of the input iterator in each bin.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::HistogramIterator;
+# fn main() {
+let v = vec![-1.0, 0.0, 0.5, 1.5, 2.0, 2.5];
+let h = v.into_iter().histogram((0.0, 2.0, 2));
+assert_eq!(h.counts(), &[2, 2]);
+assert_eq!(h.underflow(), 1);
+assert_eq!(h.overflow(), 1);
+# }
+```
+    */
+    fn histogram<B: Into<Bins>>(self, bins: B) -> Histogram where E: AsF64 {
+        let bins = bins.into();
+        let mut hist = Histogram {
+            counts: vec![0; bins.len()],
+            underflow: 0,
+            overflow: 0,
+            bins,
+        };
+        for e in self {
+            match hist.bins.bin(e.as_f64()) {
+                Bin::Under => hist.underflow += 1,
+                Bin::In(i) => hist.counts[i] += 1,
+                Bin::Over => hist.overflow += 1,
+            }
+        }
+        hist
+    }
+
+    /**
+Creates an iterator which yields each element of the input iterator, paired with the bin it falls into.
+
+The result is suitable for passing to `GroupByIterator::group_by`.
+    */
+    fn binned<B: Into<Bins>>(self, bins: B) -> Binned<Self> {
+        Binned {
+            iter: self,
+            bins: bins.into(),
+        }
+    }
+}
+
+impl<It, E> HistogramIterator<E> for It where It: Iterator<Item=E> {}
+
+/**
+Numeric types which can be binned, by converting them to `f64` as with an `as` cast.
+
+This is implemented for all of the primitive integer and floating point types, and references to them.  Integers with more than 53 significant bits are rounded to the nearest `f64`, so elements very close to a bin edge may land in the neighbouring bin.
+*/
+pub trait AsF64 {
+    /**
+Converts this value to an `f64`.
+    */
+    fn as_f64(&self) -> f64;
+}
+
+macro_rules! impl_as_f64 {
+    ($($ty:ty),*) => {
+        $(
+            impl AsF64 for $ty {
+                fn as_f64(&self) -> f64 { *self as f64 }
+            }
+        )*
+    };
+}
+
+impl_as_f64! { u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64 }
+
+impl<T> AsF64 for &T where T: AsF64 + ?Sized {
+    fn as_f64(&self) -> f64 {
+        (**self).as_f64()
+    }
+}
+
+/**
+The bin an element falls into.
+*/
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Bin {
+    /// The element is less than the lower edge of the first bin.
+    Under,
+    /// The element is in the bin with the given index.
+    In(usize),
+    /// The element is greater than the upper edge of the last bin, or is NaN.
+    Over,
+}
+
+/**
+A set of contiguous bins.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bins {
+    edges: Vec<f64>,
+    uniform: Option<(f64, f64)>,
+}
+
+impl Bins {
+    /**
+Creates `n` bins of equal width, covering the range `[min, max]`.
+
+# Failure
+
+This method will panic if `n` is zero, or if `min` is not less than `max`.
+    */
+    pub fn uniform(min: f64, max: f64, n: usize) -> Bins {
+        assert!(n > 0, "cannot create zero bins");
+        assert!(min < max, "bin range [{}, {}] is empty", min, max);
+        let width = (max - min) / n as f64;
+        let mut edges = (0..n).map(|i| min + i as f64 * width).collect::<Vec<_>>();
+        edges.push(max);
+        Bins {
+            edges,
+            uniform: Some((min, width)),
+        }
+    }
+
+    /**
+Creates bins from a sequence of edges.  `n + 1` edges define `n` bins.
+
+# Failure
+
+This method will panic if there are fewer than two edges, or if the edges are not strictly increasing.
+    */
+    pub fn edges(edges: Vec<f64>) -> Bins {
+        assert!(edges.len() >= 2, "need at least two bin edges, got {}", edges.len());
+        assert!(edges.windows(2).all(|w| w[0] < w[1]), "bin edges must be strictly increasing");
+        Bins {
+            edges,
+            uniform: None,
+        }
+    }
+
+    /**
+Returns the number of bins.
+    */
+    pub fn len(&self) -> usize {
+        self.edges.len() - 1
+    }
+
+    /**
+Returns `false`; there is always at least one bin.
+    */
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /**
+Returns the bin edges.
+    */
+    pub fn edge_values(&self) -> &[f64] {
+        &self.edges
+    }
+
+    /**
+Returns the bin that `x` falls into.
+    */
+    pub fn bin(&self, x: f64) -> Bin {
+        let n = self.len();
+        if x < self.edges[0] {
+            return Bin::Under;
+        }
+        if x > self.edges[n] || x.is_nan() {
+            return Bin::Over;
+        }
+
+        let i = match self.uniform {
+            Some((lo, width)) => {
+                // Guess from the width, then correct for any rounding error so that the result agrees with the edges.
+                let mut i = min(((x - lo) / width) as usize, n - 1);
+                while i > 0 && x < self.edges[i] {
+                    i -= 1;
+                }
+                while i + 1 < n && x >= self.edges[i + 1] {
+                    i += 1;
+                }
+                i
+            },
+            None => min(self.edges.partition_point(|&e| e <= x) - 1, n - 1),
+        };
+        Bin::In(i)
+    }
+}
+
+impl From<(f64, f64, usize)> for Bins {
+    fn from((min, max, n): (f64, f64, usize)) -> Bins {
+        Bins::uniform(min, max, n)
+    }
+}
+
+impl From<Vec<f64>> for Bins {
+    fn from(edges: Vec<f64>) -> Bins {
+        Bins::edges(edges)
+    }
+}
+
+impl<'a> From<&'a [f64]> for Bins {
+    fn from(edges: &'a [f64]) -> Bins {
+        Bins::edges(edges.to_vec())
+    }
+}
+
+/**
+The result of `HistogramIterator::histogram`.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    bins: Bins,
+    counts: Vec<usize>,
+    underflow: usize,
+    overflow: usize,
+}
+
+impl Histogram {
+    /**
+Returns the bins used to construct this histogram.
+    */
+    pub fn bins(&self) -> &Bins {
+        &self.bins
+    }
+
+    /**
+Returns the bin edges.  There is one more edge than there are bins.
+    */
+    pub fn edges(&self) -> &[f64] {
+        self.bins.edge_values()
+    }
+
+    /**
+Returns the number of elements in each bin.
+    */
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+
+    /**
+Returns the number of elements below the first bin.
+    */
+    pub fn underflow(&self) -> usize {
+        self.underflow
+    }
+
+    /**
+Returns the number of elements above the last bin, including NaNs.
+    */
+    pub fn overflow(&self) -> usize {
+        self.overflow
+    }
+
+    /**
+Returns the total number of elements counted, including underflow and overflow.
+    */
+    pub fn total(&self) -> usize {
+        self.counts.iter().fold(self.underflow + self.overflow, |a, b| a + b)
+    }
+}
+
+#[derive(Clone, Debug)]
+#[must_use = "iterator adaptors are lazy and do nothing unless consumed"]
+pub struct Binned<It> {
+    iter: It,
+    bins: Bins,
+}
+
+impl<It> Binned<It> {
+    /**
+Unwraps the iterator, returning the underlying iterator and bins.
+    */
+    pub fn unwrap(self) -> (It, Bins) {
+        let Binned { iter, bins } = self;
+        (iter, bins)
+    }
+}
+
+impl<It, E> Iterator for Binned<It> where It: Iterator<Item=E>, E: AsF64 {
+    type Item = (Bin, E);
+
+    fn next(&mut self) -> Option<(Bin, E)> {
+        self.iter.next().map(|e| (self.bins.bin(e.as_f64()), e))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<It, E> DoubleEndedIterator for Binned<It> where It: DoubleEndedIterator + Iterator<Item=E>, E: AsF64 {
+    fn next_back(&mut self) -> Option<(Bin, E)> {
+        self.iter.next_back().map(|e| (self.bins.bin(e.as_f64()), e))
+    }
+}
+
+impl<It, E> ExactSizeIterator for Binned<It> where It: ExactSizeIterator + Iterator<Item=E>, E: AsF64 {}
+
+#[test]
+fn test_histogram() {
+    let v = vec![0.0, 0.1, 0.3, 0.5, 0.99, 1.0, -0.5, 1.5, f64::NAN];
+    let h = v.into_iter().histogram((0.0, 1.0, 4));
+    assert_eq!(h.edges(), &[0.0, 0.25, 0.5, 0.75, 1.0]);
+    assert_eq!(h.counts(), &[2, 1, 1, 2]);
+    assert_eq!(h.underflow(), 1);
+    assert_eq!(h.overflow(), 2);
+    assert_eq!(h.total(), 9);
+
+    let v = vec![1u8, 2, 3, 10, 11, 99, 100, 101];
+    let h = v.into_iter().histogram(vec![1.0, 10.0, 100.0]);
+    assert_eq!(h.counts(), &[3, 4]);
+    assert_eq!(h.underflow(), 0);
+    assert_eq!(h.overflow(), 1);
+
+    // Uniform bins must agree with their edges, even when the width isn't exactly representable.
+    let bins = Bins::uniform(0.0, 1.0, 10);
+    for i in 0..1001 {
+        let x = i as f64 / 1000.0;
+        let expected = Bins::edges(bins.edge_values().to_vec()).bin(x);
+        assert_eq!(bins.bin(x), expected, "x = {}", x);
+    }
+}
+
+#[test]
+fn test_histogram_wide_integers() {
+    // Latencies in nanoseconds.
+    let v: Vec<u64> = vec![800, 1_200, 15_000, 999_999, 1_000_000, 5_000_000_000, u64::MAX];
+    let h = v.iter().histogram(vec![1_000.0, 1e4, 1e5, 1e6, 1e7]);
+    assert_eq!(h.counts(), &[1, 1, 1, 1]);
+    assert_eq!(h.underflow(), 1);
+    assert_eq!(h.overflow(), 2);
+
+    let h = v.into_iter().histogram((0.0, 2e6, 2));
+    assert_eq!(h.counts(), &[4, 1]);
+    assert_eq!(h.overflow(), 2);
+
+    let v = vec![-3i64, 0, 7, i64::MIN];
+    let h = v.into_iter().histogram((-5.0, 5.0, 2));
+    assert_eq!((h.underflow(), h.counts(), h.overflow()), (1, &[1, 1][..], 1));
+
+    let v: Vec<usize> = vec![0, 5, 10, 15];
+    let r: Vec<_> = v.iter().binned((0.0, 20.0, 2)).map(|(b, &e)| (b, e)).collect();
+    assert_eq!(r, vec![(Bin::In(0), 0), (Bin::In(0), 5), (Bin::In(1), 10), (Bin::In(1), 15)]);
+}
+
+#[test]
+fn test_binned() {
+    use super::GroupByIterator;
+
+    let v = vec![1.0f32, 2.0, 12.0, 15.0, 3.0, 35.0];
+    let r: Vec<_> = v.into_iter().binned((0.0, 30.0, 3)).collect();
+    assert_eq!(r, vec![
+        (Bin::In(0), 1.0),
+        (Bin::In(0), 2.0),
+        (Bin::In(1), 12.0),
+        (Bin::In(1), 15.0),
+        (Bin::In(0), 3.0),
+        (Bin::Over, 35.0),
+    ]);
+
+    let v = vec![1u32, 2, 12, 15, 3];
+    let r: Vec<_> = v.into_iter().binned((0.0, 30.0, 3))
+        .group_by(|&(b, _)| b)
+        .map(|(b, es)| (b, es.map(|(_, e)| e).collect::<Vec<_>>()))
+        .collect();
+    assert_eq!(r, vec![
+        (Bin::In(0), vec![1, 2]),
+        (Bin::In(1), vec![12, 15]),
+        (Bin::In(0), vec![3]),
+    ]);
+}
@@ -0,0 +1,189 @@
+/*
+Copyright ⓒ 2015 grabbag contributors.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+use std::fmt;
+
+/**
+(<em>a</em><sub>0</sub>, <em>a</em><sub>1</sub>, ..., <em>a</em><sub>n</sub>), <em>s</em>
+&nbsp;&rarr;&nbsp;
+<em>a</em><sub>0</sub> ++ <em>s</em> ++ <em>a</em><sub>1</sub> ++ <em>s</em> ++ ... ++ <em>s</em> ++ <em>a</em><sub>n</sub>
+
+*/
+pub trait IntercalateIterator<E>: Iterator + Sized where Self::Item: IntoIterator<Item=E> {
+    /**
+Creates an iterator that flattens the groups of the input iterator, yielding the elements of `sep` between each group.  `sep` will not appear at the start or end of the resulting iterator.
+
+`sep` can be anything which converts into a `Clone` iterator, such as a slice, a `Vec` or another iterator; it is cloned once for each gap.  Empty groups still get separators on either side, so `sep` may appear several times in a row.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::IntercalateIterator;
+# fn main() {
+let path = vec!["usr", "local", "bin"];
+let r: String = path.into_iter().map(|s| s.chars()).intercalate("::".chars()).collect();
+assert_eq!(r, "usr::local::bin");
+
+let rows = vec![vec![1, 2], vec![], vec![3]];
+let r: Vec<_> = rows.into_iter().intercalate(vec![0, 0]).collect();
+assert_eq!(r, vec![1, 2, 0, 0, 0, 0, 3]);
+# }
+```
+    */
+    fn intercalate<S>(self, sep: S) -> Intercalate<Self, S::IntoIter> where S: IntoIterator<Item=E>, S::IntoIter: Clone;
+}
+
+impl<It, E> IntercalateIterator<E> for It where It: Iterator, It::Item: IntoIterator<Item=E> {
+    fn intercalate<S>(self, sep: S) -> Intercalate<It, S::IntoIter> where S: IntoIterator<Item=E>, S::IntoIter: Clone {
+        Intercalate {
+            iter: self,
+            sep: sep.into_iter(),
+            cur_sep: None,
+            cur_group: None,
+            started: false,
+        }
+    }
+}
+
+/**
+An iterator which flattens groups of elements, with a separator between each group.
+
+See `IntercalateIterator::intercalate`.
+*/
+pub struct Intercalate<It, S> where It: Iterator, It::Item: IntoIterator {
+    iter: It,
+    sep: S,
+    cur_sep: Option<S>,
+    cur_group: Option<<It::Item as IntoIterator>::IntoIter>,
+    started: bool,
+}
+
+impl<It, S> Clone for Intercalate<It, S> where It: Iterator + Clone, It::Item: IntoIterator, <It::Item as IntoIterator>::IntoIter: Clone, S: Clone {
+    fn clone(&self) -> Intercalate<It, S> {
+        Intercalate {
+            iter: self.iter.clone(),
+            sep: self.sep.clone(),
+            cur_sep: self.cur_sep.clone(),
+            cur_group: self.cur_group.clone(),
+            started: self.started,
+        }
+    }
+}
+
+impl<It, S> fmt::Debug for Intercalate<It, S> where It: Iterator + fmt::Debug, It::Item: IntoIterator, <It::Item as IntoIterator>::IntoIter: fmt::Debug, S: fmt::Debug {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Intercalate")
+            .field("iter", &self.iter)
+            .field("sep", &self.sep)
+            .field("cur_sep", &self.cur_sep)
+            .field("cur_group", &self.cur_group)
+            .field("started", &self.started)
+            .finish()
+    }
+}
+
+impl<It, S, E> Iterator for Intercalate<It, S> where It: Iterator, It::Item: IntoIterator<Item=E>, S: Iterator<Item=E> + Clone {
+    type Item = E;
+
+    fn next(&mut self) -> Option<E> {
+        loop {
+            if let Some(e) = self.cur_sep.as_mut().and_then(Iterator::next) {
+                return Some(e);
+            }
+            self.cur_sep = None;
+
+            if let Some(e) = self.cur_group.as_mut().and_then(Iterator::next) {
+                return Some(e);
+            }
+            self.cur_group = None;
+
+            // The separator is only started once there is a group to follow it.
+            let group = self.iter.next()?;
+            if self.started {
+                self.cur_sep = Some(self.sep.clone());
+            }
+            self.started = true;
+            self.cur_group = Some(group.into_iter());
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (sep_lb, sep_ub) = self.cur_sep.as_ref().map_or((0, Some(0)), Iterator::size_hint);
+        let (group_lb, group_ub) = self.cur_group.as_ref().map_or((0, Some(0)), Iterator::size_hint);
+        let lb = sep_lb.saturating_add(group_lb);
+        let ub = match self.iter.size_hint() {
+            (_, Some(0)) => sep_ub.and_then(|s| group_ub.and_then(|g| s.checked_add(g))),
+            _ => None
+        };
+        (lb, ub)
+    }
+}
+
+#[test]
+fn test_intercalate() {
+    let v: Vec<Vec<i32>> = vec![];
+    let r: Vec<_> = v.into_iter().intercalate(vec![0]).collect();
+    assert_eq!(r, vec![]);
+
+    let v = vec![vec![1, 2]];
+    let r: Vec<_> = v.into_iter().intercalate(vec![0]).collect();
+    assert_eq!(r, vec![1, 2]);
+
+    let v = vec![vec![], vec![]];
+    let r: Vec<_> = v.into_iter().intercalate(vec![0]).collect();
+    assert_eq!(r, vec![0]);
+
+    // An empty separator is just `flatten`.
+    let v = vec![vec![1], vec![2, 3]];
+    let r: Vec<_> = v.into_iter().intercalate(vec![]).collect();
+    assert_eq!(r, vec![1, 2, 3]);
+
+    // Slices of slices, separated by a slice.
+    let (a, b, c) = ([1, 2], [3], [4, 5, 6]);
+    let sep = [7, 8];
+    let v: Vec<&[i32]> = vec![&a, &b, &c];
+    let r: Vec<_> = v.into_iter().intercalate(&sep).cloned().collect();
+    assert_eq!(r, vec![1, 2, 7, 8, 3, 7, 8, 4, 5, 6]);
+}
+
+#[test]
+fn test_intercalate_size_hint() {
+    let v = vec![vec![1, 2], vec![3]];
+    let mut it = v.into_iter().intercalate(vec![0, 0]);
+    assert_eq!(it.size_hint(), (0, None));
+    assert_eq!(it.next(), Some(1));
+    assert_eq!(it.size_hint(), (1, None));
+    assert_eq!(it.next(), Some(2));
+    assert_eq!(it.next(), Some(0));
+    assert_eq!(it.size_hint(), (2, Some(2)));
+    assert_eq!(it.by_ref().collect::<Vec<_>>(), vec![0, 3]);
+    assert_eq!(it.size_hint(), (0, Some(0)));
+    assert_eq!(it.next(), None);
+}
+
+#[test]
+fn test_intercalate_is_lazy() {
+    use std::cell::Cell;
+
+    let pulled = Cell::new(0);
+    let mut it = (0..3)
+        .inspect(|_| pulled.set(pulled.get() + 1))
+        .map(|i| vec![i])
+        .intercalate(vec![-1]);
+    assert_eq!(pulled.get(), 0);
+    assert_eq!(it.next(), Some(0));
+    assert_eq!(it.next(), Some(-1));
+    assert_eq!(pulled.get(), 2);
+
+    // Clones are independent.
+    let r: Vec<_> = it.clone().collect();
+    assert_eq!(r, vec![1, -1, 2]);
+    assert_eq!(it.collect::<Vec<_>>(), vec![1, -1, 2]);
+}
@@ -7,8 +7,6 @@ Licensed under the MIT license (see LICENSE or <http://opensource.org
 files in the project carrying such notice may not be copied, modified,
 or distributed except according to those terms.
 */
-use std::mem::replace;
-
 /**
 (<em>a</em><sub>0</sub>, <em>a</em><sub>1</sub>, ..., <em>a</em><sub>n</sub>), <em>i</em>
 &nbsp;&rarr;&nbsp;
@@ -20,68 +18,294 @@ pub trait IntersperseIterator<E>: Iterator<Item=E> + Sized {
 Creates an iterator that yields `inject` between each element of the input iterator.  `inject` will not appear as the first or last element of the resulting iterator.
     */
     fn intersperse(self, inject: E) -> Intersperse<Self, E>;
+
+    /**
+Creates an iterator that yields the result of calling `gap` between each element of the input iterator.  `gap` is called once for each gap, just before the gap is yielded.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::IntersperseIterator;
+# fn main() {
+let mut next_id = 100;
+let r: Vec<_> = vec![1, 2, 3].into_iter()
+    .intersperse_with(|| { next_id += 1; next_id })
+    .collect();
+assert_eq!(r, vec![1, 101, 2, 102, 3]);
+# }
+```
+    */
+    fn intersperse_with<F: FnMut() -> E>(self, gap: F) -> IntersperseWith<Self, E, F>;
+
+    /**
+Creates an iterator that yields the result of calling `gap` between each element of the input iterator.  `gap` is passed the index of the gap: zero for the gap between the first and second elements, and so on.
+    */
+    fn intersperse_with_index<F: FnMut(usize) -> E>(self, gap: F) -> IntersperseWithIndex<Self, E, F>;
 }
 
 impl<It, E> IntersperseIterator<E> for It where It: Iterator<Item=E> {
-    fn intersperse(mut self, inject: E) -> Intersperse<It, E> {
-        let look_ahead = self.next();
+    fn intersperse(self, inject: E) -> Intersperse<It, E> {
         Intersperse {
-            iter: self,
-            look_ahead: look_ahead,
-            inject: inject,
-            next_is_inject: false,
+            gaps: Gaps::new(self),
+            inject,
+        }
+    }
+
+    fn intersperse_with<F: FnMut() -> E>(self, gap: F) -> IntersperseWith<It, E, F> {
+        IntersperseWith {
+            gaps: Gaps::new(self),
+            gap,
+        }
+    }
+
+    fn intersperse_with_index<F: FnMut(usize) -> E>(self, gap: F) -> IntersperseWithIndex<It, E, F> {
+        IntersperseWithIndex {
+            gaps: Gaps::new(self),
+            gap,
         }
     }
 }
 
+/**
+An iterator which yields a fixed value between each element of another iterator.
+
+See `IntersperseIterator::intersperse`.
+*/
 #[derive(Clone, Debug)]
 pub struct Intersperse<It, E> {
-    iter: It,
-    look_ahead: Option<E>,
+    gaps: Gaps<It, E>,
     inject: E,
-    next_is_inject: bool,
+}
+
+impl<It, E> Intersperse<It, E> {
+    /**
+Unwraps the iterator, returning any element which has been pulled from the underlying iterator but not yet yielded, and the underlying iterator.
+    */
+    pub fn unwrap(self) -> (Option<E>, It) {
+        self.gaps.unwrap()
+    }
 }
 
 impl<It, E> Iterator for Intersperse<It, E> where It: Iterator<Item=E>, E: Clone {
     type Item = E;
 
     fn next(&mut self) -> Option<E> {
-        match self.next_is_inject {
-            false => match replace(&mut self.look_ahead, None) {
-                None => None,
-                Some(e) => {
-                    self.look_ahead = self.iter.next();
-                    self.next_is_inject = true;
-                    Some(e)
+        match self.gaps.next()? {
+            Piece::Item(e) => Some(e),
+            Piece::Gap(_) => Some(self.inject.clone())
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.gaps.size_hint()
+    }
+}
+
+impl<It, E> DoubleEndedIterator for Intersperse<It, E> where It: DoubleEndedIterator<Item=E> + ExactSizeIterator, E: Clone {
+    fn next_back(&mut self) -> Option<E> {
+        match self.gaps.next_back()? {
+            Piece::Item(e) => Some(e),
+            Piece::Gap(_) => Some(self.inject.clone())
+        }
+    }
+}
+
+impl<It, E> ExactSizeIterator for Intersperse<It, E> where It: ExactSizeIterator<Item=E>, E: Clone {}
+
+/*
+Something to yield next: either an element of the input, or the gap with the given index.
+*/
+enum Piece<E> {
+    Item(E),
+    Gap(usize),
+}
+
+/*
+The state shared by the interspersing iterators, which decides where the gaps go without knowing what to fill them with.
+
+Nothing is pulled from the input until the first call to `next` or `next_back`.  A gap is only yielded from the front once the element after it has been pulled, and that element is held in `peeked` until the following call.  From the back, the length of the input is used instead.
+
+`gap_front` and `gap_back` record whether a gap is owed at either end; if both are owed with no elements left between them, they are the same gap.  Gaps are numbered from zero, and `front_gaps` counts the gaps already yielded from the front.
+*/
+#[derive(Clone, Debug)]
+struct Gaps<It, E> {
+    iter: It,
+    peeked: Option<E>,
+    gap_front: bool,
+    gap_back: bool,
+    front_gaps: usize,
+}
+
+impl<It, E> Gaps<It, E> where It: Iterator<Item=E> {
+    fn new(iter: It) -> Gaps<It, E> {
+        Gaps {
+            iter,
+            peeked: None,
+            gap_front: false,
+            gap_back: false,
+            front_gaps: 0,
+        }
+    }
+
+    fn next(&mut self) -> Option<Piece<E>> {
+        match self.gap_front {
+            true => {
+                match self.iter.next() {
+                    Some(e) => self.peeked = Some(e),
+                    None if self.gap_back => self.gap_back = false,
+                    None => return None
                 }
+                self.gap_front = false;
+                self.front_gaps += 1;
+                Some(Piece::Gap(self.front_gaps - 1))
             },
-            true => match self.look_ahead.is_some() {
-                true => {
-                    self.next_is_inject = false;
-                    Some(self.inject.clone())
-                },
-                false => {
-                    None
+            false => {
+                let e = self.peeked.take().or_else(|| self.iter.next())?;
+                self.gap_front = true;
+                Some(Piece::Item(e))
+            }
+        }
+    }
+
+    /*
+Returns the number of pieces left, given the number of elements left in the underlying iterator.
+    */
+    fn pieces(&self, n: usize) -> usize {
+        let owed = self.gap_front as usize + self.gap_back as usize;
+        match n.saturating_add(self.peeked.is_some() as usize) {
+            0 => owed / 2,
+            n => (n.saturating_mul(2) - 1).saturating_add(owed)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lb, ub) = self.iter.size_hint();
+        (self.pieces(lb), ub.map(|ub| self.pieces(ub)))
+    }
+}
+
+impl<It, E> Gaps<It, E> where It: DoubleEndedIterator<Item=E> + ExactSizeIterator {
+    fn next_back(&mut self) -> Option<Piece<E>> {
+        match self.gap_back {
+            true => {
+                let elements = self.iter.len() + self.peeked.is_some() as usize;
+                let gaps = self.pieces(self.iter.len()) - elements;
+                match (elements, self.gap_front) {
+                    (0, true) => self.gap_front = false,
+                    (0, false) => return None,
+                    _ => ()
                 }
+                self.gap_back = false;
+                Some(Piece::Gap(self.front_gaps + gaps - 1))
+            },
+            false => {
+                let e = self.iter.next_back().or_else(|| self.peeked.take())?;
+                self.gap_back = true;
+                Some(Piece::Item(e))
             }
         }
     }
+}
+
+impl<It, E> Gaps<It, E> {
+    fn unwrap(self) -> (Option<E>, It) {
+        (self.peeked, self.iter)
+    }
+}
+
+/**
+An iterator which yields a generated value between each element of another iterator.
+
+See `IntersperseIterator::intersperse_with`.
+*/
+#[derive(Clone, Debug)]
+pub struct IntersperseWith<It, E, F> {
+    gaps: Gaps<It, E>,
+    gap: F,
+}
+
+impl<It, E, F> IntersperseWith<It, E, F> {
+    /**
+Unwraps the iterator, returning any element which has been pulled from the underlying iterator but not yet yielded, and the underlying iterator.
+    */
+    pub fn unwrap(self) -> (Option<E>, It) {
+        self.gaps.unwrap()
+    }
+}
+
+impl<It, E, F> Iterator for IntersperseWith<It, E, F> where It: Iterator<Item=E>, F: FnMut() -> E {
+    type Item = E;
+
+    fn next(&mut self) -> Option<E> {
+        match self.gaps.next()? {
+            Piece::Item(e) => Some(e),
+            Piece::Gap(_) => Some((self.gap)())
+        }
+    }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let (li, mui) = self.iter.size_hint();
-        let l = match li {
-            0 => 0,
-            n => n + n - 1
-        };
-        let mu = match mui {
-            Some(0) => Some(0),
-            Some(n) => Some(n + n - 1),
-            None => None
-        };
-        (l, mu)
+        self.gaps.size_hint()
     }
 }
 
+impl<It, E, F> DoubleEndedIterator for IntersperseWith<It, E, F> where It: DoubleEndedIterator<Item=E> + ExactSizeIterator, F: FnMut() -> E {
+    fn next_back(&mut self) -> Option<E> {
+        match self.gaps.next_back()? {
+            Piece::Item(e) => Some(e),
+            Piece::Gap(_) => Some((self.gap)())
+        }
+    }
+}
+
+impl<It, E, F> ExactSizeIterator for IntersperseWith<It, E, F> where It: ExactSizeIterator<Item=E>, F: FnMut() -> E {}
+
+/**
+An iterator which yields a value generated from the gap index between each element of another iterator.
+
+See `IntersperseIterator::intersperse_with_index`.
+*/
+#[derive(Clone, Debug)]
+pub struct IntersperseWithIndex<It, E, F> {
+    gaps: Gaps<It, E>,
+    gap: F,
+}
+
+impl<It, E, F> IntersperseWithIndex<It, E, F> {
+    /**
+Unwraps the iterator, returning any element which has been pulled from the underlying iterator but not yet yielded, and the underlying iterator.
+    */
+    pub fn unwrap(self) -> (Option<E>, It) {
+        self.gaps.unwrap()
+    }
+}
+
+impl<It, E, F> Iterator for IntersperseWithIndex<It, E, F> where It: Iterator<Item=E>, F: FnMut(usize) -> E {
+    type Item = E;
+
+    fn next(&mut self) -> Option<E> {
+        match self.gaps.next()? {
+            Piece::Item(e) => Some(e),
+            Piece::Gap(i) => Some((self.gap)(i))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.gaps.size_hint()
+    }
+}
+
+impl<It, E, F> DoubleEndedIterator for IntersperseWithIndex<It, E, F> where It: DoubleEndedIterator<Item=E> + ExactSizeIterator, F: FnMut(usize) -> E {
+    fn next_back(&mut self) -> Option<E> {
+        match self.gaps.next_back()? {
+            Piece::Item(e) => Some(e),
+            Piece::Gap(i) => Some((self.gap)(i))
+        }
+    }
+}
+
+impl<It, E, F> ExactSizeIterator for IntersperseWithIndex<It, E, F> where It: ExactSizeIterator<Item=E>, F: FnMut(usize) -> E {}
+
 #[test]
 fn test_intersperse() {
     let v: Vec<&str> = vec![];
@@ -100,3 +324,91 @@ fn test_intersperse() {
     let r: Vec<_> = v.into_iter().intersperse(",").collect();
     assert_eq!(r, vec!["a", ",", "b", ",", "c"]);
 }
+
+#[test]
+fn test_intersperse_with() {
+    // Neither the elements nor the separators need to be `Clone`.
+    #[derive(Debug, PartialEq)]
+    enum Token { Word(&'static str), Sep(usize) }
+
+    let mut n = 0;
+    let words = vec![Token::Word("a"), Token::Word("b"), Token::Word("c")];
+    let r: Vec<_> = IntersperseIterator::intersperse_with(words.into_iter(), || { n += 1; Token::Sep(n) })
+        .collect();
+    assert_eq!(r, vec![Token::Word("a"), Token::Sep(1), Token::Word("b"), Token::Sep(2), Token::Word("c")]);
+    assert_eq!(n, 2);
+
+    let r: Vec<_> = vec![Token::Word("a"), Token::Word("b"), Token::Word("c")].into_iter()
+        .intersperse_with_index(Token::Sep)
+        .collect();
+    assert_eq!(r, vec![Token::Word("a"), Token::Sep(0), Token::Word("b"), Token::Sep(1), Token::Word("c")]);
+
+    let mut it = (0..4).intersperse_with_index(|i| 100 + i);
+    assert_eq!(it.size_hint(), (7, Some(7)));
+    assert_eq!(it.next(), Some(0));
+    assert_eq!(it.size_hint(), (6, Some(6)));
+    assert_eq!(it.next(), Some(100));
+    assert_eq!(it.size_hint(), (5, Some(5)));
+    assert_eq!(it.unwrap(), (Some(1), 2..4));
+
+    let r: Vec<i32> = IntersperseIterator::intersperse_with(0..0, || panic!()).collect();
+    assert_eq!(r, vec![]);
+    let r: Vec<i32> = IntersperseIterator::intersperse_with(0..1, || panic!()).collect();
+    assert_eq!(r, vec![0]);
+}
+
+#[test]
+fn test_intersperse_with_is_lazy() {
+    use std::cell::Cell;
+
+    let pulled = Cell::new(0);
+    let mut it = IntersperseIterator::intersperse_with((0..3).inspect(|_| pulled.set(pulled.get() + 1)), || -1);
+    assert_eq!(pulled.get(), 0);
+    assert_eq!(it.next(), Some(0));
+    assert_eq!(pulled.get(), 1);
+}
+
+#[test]
+fn test_intersperse_is_lazy() {
+    use std::cell::Cell;
+
+    let pulled = Cell::new(0);
+    let mut it = IntersperseIterator::intersperse((0..3).inspect(|_| pulled.set(pulled.get() + 1)), -1);
+    assert_eq!(pulled.get(), 0);
+    assert_eq!(it.len(), 5);
+    assert_eq!(it.next(), Some(0));
+    assert_eq!(it.next(), Some(-1));
+    assert_eq!(pulled.get(), 2);
+    let (peeked, rest) = it.unwrap();
+    assert_eq!(peeked, Some(1));
+    assert_eq!(rest.collect::<Vec<_>>(), vec![2]);
+}
+
+#[test]
+fn test_intersperse_double_ended() {
+    // Every pattern of taking from the front (0) and from the back (1).
+    for n in 0..6 {
+        let forward: Vec<_> = (0..n).intersperse_with_index(|i| 100 + i).collect();
+        assert_eq!(forward.len(), (2 * n).saturating_sub(1));
+        for pattern in 0..1u32 << forward.len() {
+            let mut it = (0..n).intersperse_with_index(|i| 100 + i);
+            let (mut front, mut back) = (vec![], vec![]);
+            for step in 0..forward.len() {
+                assert_eq!(it.len(), forward.len() - step);
+                match pattern >> step & 1 {
+                    0 => front.push(it.next().unwrap()),
+                    _ => back.push(it.next_back().unwrap()),
+                }
+            }
+            assert_eq!(it.len(), 0);
+            assert_eq!(it.next(), None);
+            assert_eq!(it.next_back(), None);
+            back.reverse();
+            front.extend(back);
+            assert_eq!(front, forward);
+        }
+    }
+
+    let r: Vec<_> = IntersperseIterator::intersperse(vec!["a", "b", "c"].into_iter(), ",").rev().collect();
+    assert_eq!(r, vec!["c", ",", "b", ",", "a"]);
+}
@@ -0,0 +1,389 @@
+/*
+Copyright ⓒ 2015 grabbag contributors.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+use std::cmp::Ordering;
+use std::mem::replace;
+
+/**
+(<em>a</em><sub>0</sub> &le; <em>a</em><sub>1</sub> &le; ...),
+(<em>b</em><sub>0</sub> &le; <em>b</em><sub>1</sub> &le; ...)
+&nbsp;&rarr;&nbsp;
+(<em>c</em><sub>0</sub> &le; <em>c</em><sub>1</sub> &le; ...)
+
+*/
+pub trait MergeIterator<E>: Iterator<Item=E> + Sized {
+    /**
+Creates an iterator which merges two sorted iterators into a single sorted sequence.
+
+The merge is stable: where elements compare equal, those from `self` are yielded first.  If either input is not sorted, the output will not be sorted either.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::MergeIterator;
+# fn main() {
+let a = vec![1, 4, 5, 8];
+let b = vec![2, 3, 9];
+let r: Vec<_> = a.into_iter().merge(b.into_iter()).collect();
+assert_eq!(r, vec![1, 2, 3, 4, 5, 8, 9]);
+# }
+```
+    */
+    fn merge<OtherIt>(self, other: OtherIt) -> Merge<Self, OtherIt, E, NaturalOrder> where OtherIt: Iterator<Item=E>, E: Ord {
+        Merge::new(self, other, NaturalOrder)
+    }
+
+    /**
+Creates an iterator which merges two iterators, each sorted according to `compare`, into a single sorted sequence.
+    */
+    fn merge_by<OtherIt, F>(self, other: OtherIt, compare: F) -> Merge<Self, OtherIt, E, F> where OtherIt: Iterator<Item=E>, F: FnMut(&E, &E) -> Ordering {
+        Merge::new(self, other, compare)
+    }
+
+    /**
+Creates an iterator which merges two iterators, each sorted by the key computed by `key`, into a single sorted sequence.
+    */
+    fn merge_by_key<OtherIt, K, F>(self, other: OtherIt, key: F) -> Merge<Self, OtherIt, E, KeyOrder<F>> where OtherIt: Iterator<Item=E>, K: Ord, F: FnMut(&E) -> K {
+        Merge::new(self, other, KeyOrder(key))
+    }
+
+    /**
+Creates an iterator which merges any number of sorted sequences into a single sorted sequence.
+
+This keeps the head of each input in a binary heap, so each element requires <em>O</em>(log <em>k</em>) comparisons for <em>k</em> inputs.  The merge is stable: where elements compare equal, those from earlier inputs are yielded first.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::MergeIterator;
+# fn main() {
+let vs = vec![vec![1, 4, 7], vec![2, 5, 8], vec![0, 3, 6, 9]];
+let r: Vec<_> = vs.into_iter().kmerge().collect();
+assert_eq!(r, (0..10).collect::<Vec<_>>());
+# }
+```
+    */
+    fn kmerge(self) -> KMerge<E::IntoIter, E::Item, NaturalOrder> where E: IntoIterator, E::Item: Ord {
+        KMerge::new(self, NaturalOrder)
+    }
+
+    /**
+Creates an iterator which merges any number of sequences, each sorted according to `compare`, into a single sorted sequence.
+    */
+    fn kmerge_by<F>(self, compare: F) -> KMerge<E::IntoIter, E::Item, F> where E: IntoIterator, F: FnMut(&E::Item, &E::Item) -> Ordering {
+        KMerge::new(self, compare)
+    }
+
+    /**
+Creates an iterator which merges any number of sequences, each sorted by the key computed by `key`, into a single sorted sequence.
+    */
+    fn kmerge_by_key<K, F>(self, key: F) -> KMerge<E::IntoIter, E::Item, KeyOrder<F>> where E: IntoIterator, K: Ord, F: FnMut(&E::Item) -> K {
+        KMerge::new(self, KeyOrder(key))
+    }
+}
+
+impl<It, E> MergeIterator<E> for It where It: Iterator<Item=E> {}
+
+/**
+A comparison used to order elements.
+
+This is implemented for closures of type `FnMut(&E, &E) -> Ordering`, as well as `NaturalOrder` and `KeyOrder`.
+*/
+pub trait Compare<E> {
+    /**
+Compares two elements.
+    */
+    fn compare(&mut self, a: &E, b: &E) -> Ordering;
+}
+
+impl<E, F> Compare<E> for F where F: FnMut(&E, &E) -> Ordering {
+    fn compare(&mut self, a: &E, b: &E) -> Ordering {
+        self(a, b)
+    }
+}
+
+/**
+Compares elements using their `Ord` implementation.
+*/
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NaturalOrder;
+
+impl<E> Compare<E> for NaturalOrder where E: Ord {
+    fn compare(&mut self, a: &E, b: &E) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+/**
+Compares elements by the `Ord` implementation of a key computed from each.
+*/
+#[derive(Clone, Debug)]
+pub struct KeyOrder<F>(pub F);
+
+impl<E, K, F> Compare<E> for KeyOrder<F> where K: Ord, F: FnMut(&E) -> K {
+    fn compare(&mut self, a: &E, b: &E) -> Ordering {
+        (self.0)(a).cmp(&(self.0)(b))
+    }
+}
+
+#[derive(Clone, Debug)]
+#[must_use = "iterator adaptors are lazy and do nothing unless consumed"]
+pub struct Merge<LeftIt, RightIt, E, C> {
+    left: LeftIt,
+    right: RightIt,
+    left_head: Option<E>,
+    right_head: Option<E>,
+    left_done: bool,
+    right_done: bool,
+    compare: C,
+}
+
+impl<LeftIt, RightIt, E, C> Merge<LeftIt, RightIt, E, C> {
+    fn new(left: LeftIt, right: RightIt, compare: C) -> Self {
+        Merge {
+            left,
+            right,
+            left_head: None,
+            right_head: None,
+            left_done: false,
+            right_done: false,
+            compare,
+        }
+    }
+
+    /**
+Unwraps the iterator, returning the underlying iterators, along with any element which has been pulled from each but not yet yielded.
+    */
+    pub fn unwrap(self) -> ((Option<E>, LeftIt), (Option<E>, RightIt)) {
+        let Merge { left, right, left_head, right_head, .. } = self;
+        ((left_head, left), (right_head, right))
+    }
+}
+
+impl<LeftIt, RightIt, E, C> Iterator for Merge<LeftIt, RightIt, E, C> where LeftIt: Iterator<Item=E>, RightIt: Iterator<Item=E>, C: Compare<E> {
+    type Item = E;
+
+    fn next(&mut self) -> Option<E> {
+        // Inputs are not polled again once exhausted, so they need not be fused.
+        if self.left_head.is_none() && !self.left_done {
+            self.left_head = self.left.next();
+            self.left_done = self.left_head.is_none();
+        }
+        if self.right_head.is_none() && !self.right_done {
+            self.right_head = self.right.next();
+            self.right_done = self.right_head.is_none();
+        }
+
+        let take_left = match (&self.left_head, &self.right_head) {
+            (Some(l), Some(r)) => self.compare.compare(l, r) != Ordering::Greater,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        if take_left {
+            self.left_head.take()
+        } else {
+            self.right_head.take()
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let heads = self.left_head.iter().count() + self.right_head.iter().count();
+        let hint = |done: bool, hint: (usize, Option<usize>)| match done {
+            true => (0, Some(0)),
+            false => hint
+        };
+        let (l0, mu0) = hint(self.left_done, self.left.size_hint());
+        let (l1, mu1) = hint(self.right_done, self.right.size_hint());
+        let mu = match (mu0, mu1) {
+            (Some(u0), Some(u1)) => u0.checked_add(u1).and_then(|u| u.checked_add(heads)),
+            _ => None
+        };
+        (l0.saturating_add(l1).saturating_add(heads), mu)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct HeadTail<It, E> {
+    head: E,
+    tail: It,
+    idx: usize,
+}
+
+#[derive(Clone, Debug)]
+#[must_use = "iterator adaptors are lazy and do nothing unless consumed"]
+pub struct KMerge<It, E, C> {
+    heap: Vec<HeadTail<It, E>>,
+    compare: C,
+}
+
+impl<It, E, C> KMerge<It, E, C> where It: Iterator<Item=E>, C: Compare<E> {
+    /**
+Creates an iterator which merges the sequences in `its`, each sorted according to `compare`, into a single sorted sequence.
+
+This is equivalent to `MergeIterator::kmerge_by`, but accepts any `Compare` implementation.
+    */
+    pub fn new<Its>(its: Its, compare: C) -> Self where Its: Iterator, Its::Item: IntoIterator<IntoIter=It, Item=E> {
+        let mut kmerge = KMerge {
+            heap: vec![],
+            compare,
+        };
+        for (idx, it) in its.enumerate() {
+            let mut tail = it.into_iter();
+            if let Some(head) = tail.next() {
+                kmerge.heap.push(HeadTail { head, tail, idx });
+            }
+        }
+        for i in (0..kmerge.heap.len() / 2).rev() {
+            kmerge.sift_down(i);
+        }
+        kmerge
+    }
+
+    fn less(&mut self, i: usize, j: usize) -> bool {
+        let (a, b) = (&self.heap[i], &self.heap[j]);
+        match self.compare.compare(&a.head, &b.head) {
+            Ordering::Equal => a.idx < b.idx,
+            ord => ord == Ordering::Less
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize) {
+        loop {
+            let (l, r) = (2*i + 1, 2*i + 2);
+            let mut least = i;
+            if l < self.heap.len() && self.less(l, least) {
+                least = l;
+            }
+            if r < self.heap.len() && self.less(r, least) {
+                least = r;
+            }
+            if least == i {
+                break;
+            }
+            self.heap.swap(i, least);
+            i = least;
+        }
+    }
+}
+
+impl<It, E, C> KMerge<It, E, C> {
+    /**
+Unwraps the iterator, returning the remaining input iterators, each paired with the element which has been pulled from it but not yet yielded.
+
+The inputs are not returned in any particular order.  Exhausted inputs are not returned.
+    */
+    pub fn unwrap(self) -> Vec<(E, It)> {
+        self.heap.into_iter().map(|ht| (ht.head, ht.tail)).collect()
+    }
+}
+
+impl<It, E, C> Iterator for KMerge<It, E, C> where It: Iterator<Item=E>, C: Compare<E> {
+    type Item = E;
+
+    fn next(&mut self) -> Option<E> {
+        if self.heap.is_empty() {
+            return None;
+        }
+
+        let e = match self.heap[0].tail.next() {
+            Some(next) => replace(&mut self.heap[0].head, next),
+            None => self.heap.swap_remove(0).head
+        };
+        self.sift_down(0);
+        Some(e)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.heap.iter().fold((0, Some(0)), |(lb, mub), ht| {
+            let (l, mu) = ht.tail.size_hint();
+            let mub = match (mub, mu) {
+                (Some(a), Some(b)) => a.checked_add(b).and_then(|u| u.checked_add(1)),
+                _ => None
+            };
+            (lb.saturating_add(l).saturating_add(1), mub)
+        })
+    }
+}
+
+#[test]
+fn test_merge() {
+    let a = vec![1, 3, 3, 7];
+    let b = vec![0, 3, 8, 9];
+    let it = a.into_iter().merge(b.into_iter());
+    assert_eq!(it.size_hint(), (8, Some(8)));
+    assert_eq!(it.collect::<Vec<_>>(), vec![0, 1, 3, 3, 3, 7, 8, 9]);
+
+    let a: Vec<i32> = vec![];
+    let b = vec![1, 2];
+    let r: Vec<_> = a.into_iter().merge(b.into_iter()).collect();
+    assert_eq!(r, vec![1, 2]);
+
+    // Stability: equal elements from the left come first.
+    let a = vec![(1, 'a'), (2, 'a')];
+    let b = vec![(1, 'b'), (2, 'b')];
+    let r: Vec<_> = a.into_iter().merge_by_key(b.into_iter(), |e| e.0).collect();
+    assert_eq!(r, vec![(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')]);
+
+    let a = vec![9, 5, 1];
+    let b = vec![8, 2];
+    let r: Vec<_> = a.into_iter().merge_by(b.into_iter(), |a, b| b.cmp(a)).collect();
+    assert_eq!(r, vec![9, 8, 5, 2, 1]);
+}
+
+#[test]
+fn test_merge_unfused() {
+    // Yields `None` on every third call, and elements otherwise.
+    struct Flaky(u32);
+    impl Iterator for Flaky {
+        type Item = u32;
+        fn next(&mut self) -> Option<u32> {
+            self.0 += 1;
+            match self.0 % 3 {
+                0 => None,
+                _ => Some(self.0)
+            }
+        }
+    }
+
+    let mut it = Flaky(0).merge(vec![0, 10].into_iter());
+    assert_eq!(it.by_ref().collect::<Vec<_>>(), vec![0, 1, 2, 10]);
+    assert_eq!(it.size_hint(), (0, Some(0)));
+    assert_eq!(it.next(), None);
+    assert_eq!(it.next(), None);
+
+    let mut it = vec![5].into_iter().merge(Flaky(0));
+    assert_eq!(it.by_ref().collect::<Vec<_>>(), vec![1, 2, 5]);
+    assert_eq!(it.next(), None);
+}
+
+#[test]
+fn test_kmerge() {
+    let vs: Vec<Vec<u32>> = vec![];
+    assert_eq!(vs.into_iter().kmerge().next(), None);
+
+    let vs = vec![vec![3, 6, 9], vec![], vec![1, 2, 10, 11], vec![0, 4, 5, 7, 8]];
+    let it = vs.into_iter().kmerge();
+    assert_eq!(it.size_hint(), (12, Some(12)));
+    assert_eq!(it.collect::<Vec<_>>(), (0..12).collect::<Vec<_>>());
+
+    let vs = vec![
+        vec![(0, 'a'), (1, 'a'), (1, 'a')],
+        vec![(1, 'b'), (2, 'b')],
+        vec![(0, 'c'), (1, 'c')],
+    ];
+    let r: Vec<_> = vs.into_iter().kmerge_by_key(|e| e.0).collect();
+    assert_eq!(r, vec![(0, 'a'), (0, 'c'), (1, 'a'), (1, 'a'), (1, 'b'), (1, 'c'), (2, 'b')]);
+
+    let vs = vec![vec![5, 3, 1], vec![6, 4, 2]];
+    let r: Vec<_> = vs.into_iter().kmerge_by(|a, b| b.cmp(a)).collect();
+    assert_eq!(r, vec![6, 5, 4, 3, 2, 1]);
+}
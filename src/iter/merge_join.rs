@@ -0,0 +1,630 @@
+/*
+Copyright ⓒ 2015 grabbag contributors.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+use std::cmp::{max, Ordering};
+
+/**
+Pairs up the elements of two sequences which are sorted by a common key.
+
+In each case, `compare` compares the key of an element from the left sequence with the key of an element from the right sequence.  Both sequences must be sorted by their keys.
+*/
+pub trait MergeJoinIterator<L>: Iterator<Item=L> + Sized {
+    /**
+Creates an iterator which walks two sorted sequences in step, yielding elements which have matching keys together, and all other elements on their own.
+
+Where a key appears several times, elements are paired up one-to-one, in order; any surplus elements on either side are yielded on their own.  For the usual relational behaviour, where each element is paired with every matching element, use one of the `*_join` methods instead.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::MergeJoinIterator;
+# use grabbag::iter::merge_join::EitherOrBoth::{Left, Right, Both};
+# fn main() {
+let a = vec![1, 2, 4];
+let b = vec!["2", "3", "4"];
+let r: Vec<_> = a.into_iter()
+    .merge_join_by(b.into_iter(), |l, r| l.cmp(&r.parse().unwrap()))
+    .collect();
+assert_eq!(r, vec![Left(1), Both(2, "2"), Right("3"), Both(4, "4")]);
+# }
+```
+    */
+    fn merge_join_by<RightIt, F>(self, right: RightIt, compare: F) -> MergeJoinBy<Self, RightIt, L, RightIt::Item, F> where RightIt: Iterator, F: FnMut(&L, &RightIt::Item) -> Ordering {
+        MergeJoinBy {
+            left: self,
+            right,
+            left_head: None,
+            right_head: None,
+            left_done: false,
+            right_done: false,
+            compare,
+        }
+    }
+
+    /**
+Creates an iterator which yields every pair of elements, one from each sequence, with matching keys.
+
+If a key appears <em>m</em> times on the left and <em>n</em> times on the right, <em>m</em> &times; <em>n</em> pairs are yielded for that key, grouped by left element.  To do this, each run of matching elements from the right is buffered and cloned.
+    */
+    fn inner_join<RightIt, F>(self, right: RightIt, compare: F) -> InnerJoin<Self, RightIt, L, RightIt::Item, F> where RightIt: Iterator, F: FnMut(&L, &RightIt::Item) -> Ordering, L: Clone, RightIt::Item: Clone {
+        InnerJoin {
+            join: Join::new(JoinKind::Inner, self, right, compare),
+        }
+    }
+
+    /**
+Creates an iterator which yields every pair of elements, one from each sequence, with matching keys, along with every element from the left which has no match.
+
+Duplicate keys are handled in the same way as `inner_join`.
+    */
+    fn left_join<RightIt, F>(self, right: RightIt, compare: F) -> LeftJoin<Self, RightIt, L, RightIt::Item, F> where RightIt: Iterator, F: FnMut(&L, &RightIt::Item) -> Ordering, L: Clone, RightIt::Item: Clone {
+        LeftJoin {
+            join: Join::new(JoinKind::Left, self, right, compare),
+        }
+    }
+
+    /**
+Creates an iterator which yields every pair of elements, one from each sequence, with matching keys, along with every element from either side which has no match.
+
+Duplicate keys are handled in the same way as `inner_join`.
+    */
+    fn outer_join<RightIt, F>(self, right: RightIt, compare: F) -> OuterJoin<Self, RightIt, L, RightIt::Item, F> where RightIt: Iterator, F: FnMut(&L, &RightIt::Item) -> Ordering, L: Clone, RightIt::Item: Clone {
+        OuterJoin {
+            join: Join::new(JoinKind::Outer, self, right, compare),
+        }
+    }
+}
+
+impl<It, L> MergeJoinIterator<L> for It where It: Iterator<Item=L> {}
+
+/**
+An element from the left sequence, the right sequence, or a pair of matching elements from both.
+*/
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum EitherOrBoth<L, R> {
+    /// An element which only appeared in the left sequence.
+    Left(L),
+    /// An element which only appeared in the right sequence.
+    Right(R),
+    /// Matching elements from both sequences.
+    Both(L, R),
+}
+
+impl<L, R> EitherOrBoth<L, R> {
+    /**
+Converts into a pair of options.
+    */
+    pub fn into_options(self) -> (Option<L>, Option<R>) {
+        match self {
+            EitherOrBoth::Left(l) => (Some(l), None),
+            EitherOrBoth::Right(r) => (None, Some(r)),
+            EitherOrBoth::Both(l, r) => (Some(l), Some(r)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[must_use = "iterator adaptors are lazy and do nothing unless consumed"]
+pub struct MergeJoinBy<LeftIt, RightIt, L, R, F> {
+    left: LeftIt,
+    right: RightIt,
+    left_head: Option<L>,
+    right_head: Option<R>,
+    left_done: bool,
+    right_done: bool,
+    compare: F,
+}
+
+impl<LeftIt, RightIt, L, R, F> MergeJoinBy<LeftIt, RightIt, L, R, F> {
+    /**
+Unwraps the iterator, returning the underlying iterators, along with any element which has been pulled from each but not yet yielded.
+    */
+    pub fn unwrap(self) -> ((Option<L>, LeftIt), (Option<R>, RightIt)) {
+        let MergeJoinBy { left, right, left_head, right_head, .. } = self;
+        ((left_head, left), (right_head, right))
+    }
+}
+
+impl<LeftIt, RightIt, L, R, F> Iterator for MergeJoinBy<LeftIt, RightIt, L, R, F> where LeftIt: Iterator<Item=L>, RightIt: Iterator<Item=R>, F: FnMut(&L, &R) -> Ordering {
+    type Item = EitherOrBoth<L, R>;
+
+    fn next(&mut self) -> Option<EitherOrBoth<L, R>> {
+        // Inputs are not polled again once exhausted, so they need not be fused.
+        if self.left_head.is_none() && !self.left_done {
+            self.left_head = self.left.next();
+            self.left_done = self.left_head.is_none();
+        }
+        if self.right_head.is_none() && !self.right_done {
+            self.right_head = self.right.next();
+            self.right_done = self.right_head.is_none();
+        }
+
+        let ord = match (&self.left_head, &self.right_head) {
+            (Some(l), Some(r)) => (self.compare)(l, r),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => return None,
+        };
+
+        match ord {
+            Ordering::Less => self.left_head.take().map(EitherOrBoth::Left),
+            Ordering::Greater => self.right_head.take().map(EitherOrBoth::Right),
+            Ordering::Equal => match (self.left_head.take(), self.right_head.take()) {
+                (Some(l), Some(r)) => Some(EitherOrBoth::Both(l, r)),
+                _ => unreachable!()
+            },
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let h0 = self.left_head.iter().count();
+        let h1 = self.right_head.iter().count();
+        let (l0, mu0) = remaining(self.left_done, &self.left);
+        let (l1, mu1) = remaining(self.right_done, &self.right);
+        let mu = match (mu0, mu1) {
+            (Some(u0), Some(u1)) => u0.checked_add(u1).and_then(|u| u.checked_add(h0 + h1)),
+            _ => None
+        };
+        (max(l0.saturating_add(h0), l1.saturating_add(h1)), mu)
+    }
+}
+
+/*
+Returns the size hint of an input, which is empty once it has returned `None`.
+*/
+fn remaining<It>(done: bool, it: &It) -> (usize, Option<usize>) where It: Iterator {
+    match done {
+        true => (0, Some(0)),
+        false => it.size_hint()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum JoinKind {
+    Inner,
+    Left,
+    Outer,
+}
+
+/*
+The shared implementation of the relational joins.
+
+`group` holds the current run of right elements which match `left_cur`.  Each left element with the same key is paired with every element of `group` in turn.
+*/
+#[derive(Clone, Debug)]
+struct Join<LeftIt, RightIt, L, R, F> {
+    kind: JoinKind,
+    left: LeftIt,
+    right: RightIt,
+    left_head: Option<L>,
+    right_head: Option<R>,
+    left_done: bool,
+    right_done: bool,
+    started: bool,
+    left_cur: Option<L>,
+    group: Vec<R>,
+    group_pos: usize,
+    compare: F,
+}
+
+impl<LeftIt, RightIt, L, R, F> Join<LeftIt, RightIt, L, R, F> where LeftIt: Iterator<Item=L>, RightIt: Iterator<Item=R>, F: FnMut(&L, &R) -> Ordering, L: Clone, R: Clone {
+    fn new(kind: JoinKind, left: LeftIt, right: RightIt, compare: F) -> Self {
+        Join {
+            kind,
+            left,
+            right,
+            left_head: None,
+            right_head: None,
+            left_done: false,
+            right_done: false,
+            started: false,
+            left_cur: None,
+            group: vec![],
+            group_pos: 0,
+            compare,
+        }
+    }
+
+    // Inputs are not polled again once exhausted, so they need not be fused.
+    fn next_left(&mut self) -> Option<L> {
+        if self.left_done {
+            return None;
+        }
+        let l = self.left.next();
+        self.left_done = l.is_none();
+        l
+    }
+
+    fn next_right(&mut self) -> Option<R> {
+        if self.right_done {
+            return None;
+        }
+        let r = self.right.next();
+        self.right_done = r.is_none();
+        r
+    }
+
+    fn next(&mut self) -> Option<EitherOrBoth<L, R>> {
+        if !self.started {
+            self.left_head = self.next_left();
+            self.right_head = self.next_right();
+            self.started = true;
+        }
+
+        loop {
+            if let Some(ref l) = self.left_cur {
+                if self.group_pos < self.group.len() {
+                    self.group_pos += 1;
+                    return Some(EitherOrBoth::Both(l.clone(), self.group[self.group_pos - 1].clone()));
+                }
+            }
+
+            if self.left_cur.is_some() {
+                // Done with this left element; if the next one has the same key, pair it with the same group.
+                self.left_cur = None;
+                let same_key = match (&self.left_head, self.group.first()) {
+                    (Some(l), Some(r)) => (self.compare)(l, r) == Ordering::Equal,
+                    _ => false
+                };
+                if same_key {
+                    self.left_cur = self.left_head.take();
+                    self.left_head = self.next_left();
+                    self.group_pos = 0;
+                    continue;
+                }
+                self.group.clear();
+            }
+
+            let ord = match (&self.left_head, &self.right_head) {
+                (Some(l), Some(r)) => (self.compare)(l, r),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => return None,
+            };
+
+            match ord {
+                Ordering::Less => {
+                    let l = self.left_head.take();
+                    self.left_head = self.next_left();
+                    match self.kind {
+                        JoinKind::Inner if self.right_head.is_none() => return None,
+                        JoinKind::Inner => (),
+                        _ => return l.map(EitherOrBoth::Left),
+                    }
+                },
+                Ordering::Greater => {
+                    let r = self.right_head.take();
+                    self.right_head = self.next_right();
+                    match self.kind {
+                        JoinKind::Outer => return r.map(EitherOrBoth::Right),
+                        _ if self.left_head.is_none() => return None,
+                        _ => (),
+                    }
+                },
+                Ordering::Equal => {
+                    // Collect every right element which matches this left element.
+                    let l = self.left_head.take().unwrap();
+                    while let Some(r) = self.right_head.take() {
+                        if (self.compare)(&l, &r) != Ordering::Equal {
+                            self.right_head = Some(r);
+                            break;
+                        }
+                        self.group.push(r);
+                        self.right_head = self.next_right();
+                    }
+                    self.left_cur = Some(l);
+                    self.left_head = self.next_left();
+                    self.group_pos = 0;
+                },
+            }
+        }
+    }
+}
+
+impl<LeftIt, RightIt, L, R, F> Join<LeftIt, RightIt, L, R, F> where LeftIt: Iterator<Item=L>, RightIt: Iterator<Item=R> {
+    /*
+`pending` is the number of pairs left for the current left element.  Every other left element is paired with at most `max_matches` elements, from the current group and the remaining right elements, or is yielded on its own.
+    */
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = match self.left_cur {
+            Some(_) => self.group.len() - self.group_pos,
+            None => 0
+        };
+        let with_head = |(lb, ub): (usize, Option<usize>), head: bool| {
+            let head = head as usize;
+            (lb.saturating_add(head), ub.and_then(|ub| ub.checked_add(head)))
+        };
+        let lefts = with_head(remaining(self.left_done, &self.left), self.left_head.is_some());
+        let rights = with_head(remaining(self.right_done, &self.right), self.right_head.is_some());
+        let max_matches = rights.1.and_then(|r| r.checked_add(self.group.len()));
+
+        let per_left = |at_least_one: bool| max_matches.map(|m| match at_least_one {
+            true => max(m, 1),
+            false => m
+        });
+        let pairs = |at_least_one: bool| lefts.1
+            .and_then(|l| per_left(at_least_one).and_then(|m| l.checked_mul(m)))
+            .and_then(|n| n.checked_add(pending));
+        match self.kind {
+            JoinKind::Inner => (0, pairs(false)),
+            JoinKind::Left => (lefts.0.saturating_add(pending), pairs(true)),
+            JoinKind::Outer => (
+                max(lefts.0, rights.0).saturating_add(pending),
+                pairs(true).and_then(|n| rights.1.and_then(|r| n.checked_add(r))),
+            ),
+        }
+    }
+}
+
+impl<LeftIt, RightIt, L, R, F> Join<LeftIt, RightIt, L, R, F> {
+    fn unwrap(self) -> ((Option<L>, LeftIt), (Option<R>, RightIt)) {
+        let Join { left, right, left_head, right_head, .. } = self;
+        ((left_head, left), (right_head, right))
+    }
+}
+
+/**
+An iterator over the matching pairs of two sorted sequences.
+*/
+#[derive(Clone, Debug)]
+#[must_use = "iterator adaptors are lazy and do nothing unless consumed"]
+pub struct InnerJoin<LeftIt, RightIt, L, R, F> {
+    join: Join<LeftIt, RightIt, L, R, F>,
+}
+
+impl<LeftIt, RightIt, L, R, F> InnerJoin<LeftIt, RightIt, L, R, F> {
+    /**
+Unwraps the iterator, returning the underlying iterators, along with any element which has been pulled from each but not yet compared.
+
+Elements which have already been matched, but not yet yielded in every pair, are discarded.
+    */
+    pub fn unwrap(self) -> ((Option<L>, LeftIt), (Option<R>, RightIt)) {
+        self.join.unwrap()
+    }
+}
+
+impl<LeftIt, RightIt, L, R, F> Iterator for InnerJoin<LeftIt, RightIt, L, R, F> where LeftIt: Iterator<Item=L>, RightIt: Iterator<Item=R>, F: FnMut(&L, &R) -> Ordering, L: Clone, R: Clone {
+    type Item = (L, R);
+
+    fn next(&mut self) -> Option<(L, R)> {
+        loop {
+            match self.join.next() {
+                Some(EitherOrBoth::Both(l, r)) => return Some((l, r)),
+                Some(_) => (),
+                None => return None,
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.join.size_hint()
+    }
+}
+
+/**
+An iterator over the matching pairs of two sorted sequences, along with the unmatched elements of the left sequence.
+*/
+#[derive(Clone, Debug)]
+#[must_use = "iterator adaptors are lazy and do nothing unless consumed"]
+pub struct LeftJoin<LeftIt, RightIt, L, R, F> {
+    join: Join<LeftIt, RightIt, L, R, F>,
+}
+
+impl<LeftIt, RightIt, L, R, F> LeftJoin<LeftIt, RightIt, L, R, F> {
+    /**
+Unwraps the iterator, returning the underlying iterators, along with any element which has been pulled from each but not yet compared.
+
+Elements which have already been matched, but not yet yielded in every pair, are discarded.
+    */
+    pub fn unwrap(self) -> ((Option<L>, LeftIt), (Option<R>, RightIt)) {
+        self.join.unwrap()
+    }
+}
+
+impl<LeftIt, RightIt, L, R, F> Iterator for LeftJoin<LeftIt, RightIt, L, R, F> where LeftIt: Iterator<Item=L>, RightIt: Iterator<Item=R>, F: FnMut(&L, &R) -> Ordering, L: Clone, R: Clone {
+    type Item = (L, Option<R>);
+
+    fn next(&mut self) -> Option<(L, Option<R>)> {
+        loop {
+            match self.join.next() {
+                Some(EitherOrBoth::Both(l, r)) => return Some((l, Some(r))),
+                Some(EitherOrBoth::Left(l)) => return Some((l, None)),
+                Some(EitherOrBoth::Right(_)) => (),
+                None => return None,
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.join.size_hint()
+    }
+}
+
+/**
+An iterator over the matching pairs of two sorted sequences, along with the unmatched elements of either sequence.
+*/
+#[derive(Clone, Debug)]
+#[must_use = "iterator adaptors are lazy and do nothing unless consumed"]
+pub struct OuterJoin<LeftIt, RightIt, L, R, F> {
+    join: Join<LeftIt, RightIt, L, R, F>,
+}
+
+impl<LeftIt, RightIt, L, R, F> OuterJoin<LeftIt, RightIt, L, R, F> {
+    /**
+Unwraps the iterator, returning the underlying iterators, along with any element which has been pulled from each but not yet compared.
+
+Elements which have already been matched, but not yet yielded in every pair, are discarded.
+    */
+    pub fn unwrap(self) -> ((Option<L>, LeftIt), (Option<R>, RightIt)) {
+        self.join.unwrap()
+    }
+}
+
+impl<LeftIt, RightIt, L, R, F> Iterator for OuterJoin<LeftIt, RightIt, L, R, F> where LeftIt: Iterator<Item=L>, RightIt: Iterator<Item=R>, F: FnMut(&L, &R) -> Ordering, L: Clone, R: Clone {
+    type Item = EitherOrBoth<L, R>;
+
+    fn next(&mut self) -> Option<EitherOrBoth<L, R>> {
+        self.join.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.join.size_hint()
+    }
+}
+
+#[test]
+fn test_merge_join_by() {
+    use self::EitherOrBoth::{Left, Right, Both};
+
+    let a = vec![1, 2, 2, 2, 5];
+    let b = vec![0, 2, 2, 5, 6];
+    let it = a.into_iter().merge_join_by(b.into_iter(), |l, r| l.cmp(r));
+    assert_eq!(it.size_hint(), (5, Some(10)));
+    assert_eq!(it.collect::<Vec<_>>(), vec![
+        Right(0), Left(1), Both(2, 2), Both(2, 2), Left(2), Both(5, 5), Right(6),
+    ]);
+
+    let a: Vec<i32> = vec![];
+    let b = vec![1];
+    let r: Vec<_> = a.into_iter().merge_join_by(b.into_iter(), |l, r| l.cmp(r)).collect();
+    assert_eq!(r, vec![Right(1)]);
+}
+
+#[test]
+fn test_joins() {
+    use self::EitherOrBoth::{Left, Right, Both};
+
+    let a = vec![(1, "a1"), (2, "a2"), (2, "a2'"), (4, "a4"), (5, "a5")];
+    let b = vec![(0, "b0"), (2, "b2"), (2, "b2'"), (3, "b3"), (4, "b4"), (6, "b6")];
+    let key = |l: &(i32, &str), r: &(i32, &str)| l.0.cmp(&r.0);
+
+    let r: Vec<_> = a.clone().into_iter().inner_join(b.clone().into_iter(), key)
+        .map(|(l, r)| (l.1, r.1)).collect();
+    assert_eq!(r, vec![
+        ("a2", "b2"), ("a2", "b2'"), ("a2'", "b2"), ("a2'", "b2'"), ("a4", "b4"),
+    ]);
+
+    let r: Vec<_> = a.clone().into_iter().left_join(b.clone().into_iter(), key)
+        .map(|(l, r)| (l.1, r.map(|r| r.1))).collect();
+    assert_eq!(r, vec![
+        ("a1", None),
+        ("a2", Some("b2")), ("a2", Some("b2'")), ("a2'", Some("b2")), ("a2'", Some("b2'")),
+        ("a4", Some("b4")),
+        ("a5", None),
+    ]);
+
+    let r: Vec<_> = a.into_iter().outer_join(b.into_iter(), key)
+        .map(|e| match e {
+            Left(l) => Left(l.1),
+            Right(r) => Right(r.1),
+            Both(l, r) => Both(l.1, r.1),
+        }).collect();
+    assert_eq!(r, vec![
+        Right("b0"), Left("a1"),
+        Both("a2", "b2"), Both("a2", "b2'"), Both("a2'", "b2"), Both("a2'", "b2'"),
+        Right("b3"), Both("a4", "b4"), Left("a5"), Right("b6"),
+    ]);
+
+    let a = vec![1, 1, 3];
+    let b = vec![1, 2, 3, 3];
+    let r: Vec<_> = a.into_iter().outer_join(b.into_iter(), |l, r| l.cmp(r)).collect();
+    assert_eq!(r, vec![Both(1, 1), Both(1, 1), Right(2), Both(3, 3), Both(3, 3)]);
+}
+
+#[cfg(test)]
+struct Unfused<'a> {
+    items: Vec<Option<i32>>,
+    polls_after_none: &'a ::std::cell::Cell<usize>,
+    seen_none: bool,
+}
+
+#[cfg(test)]
+impl<'a> Iterator for Unfused<'a> {
+    type Item = i32;
+
+    fn next(&mut self) -> Option<i32> {
+        if self.seen_none {
+            self.polls_after_none.set(self.polls_after_none.get() + 1);
+        }
+        let e = match self.items.is_empty() {
+            true => None,
+            false => self.items.remove(0)
+        };
+        self.seen_none |= e.is_none();
+        e
+    }
+}
+
+#[test]
+fn test_merge_join_unfused() {
+    use std::cell::Cell;
+    use self::EitherOrBoth::{Left, Right, Both};
+
+    // An empty left input which would produce more elements if polled again.
+    let polls = Cell::new(0);
+    let left = Unfused { items: vec![None, Some(3), Some(4)], polls_after_none: &polls, seen_none: false };
+    let r: Vec<_> = left.merge_join_by(1..6, |l, r| l.cmp(r)).collect();
+    assert_eq!(r, vec![Right(1), Right(2), Right(3), Right(4), Right(5)]);
+    assert_eq!(polls.get(), 0);
+
+    let polls = Cell::new(0);
+    let right = Unfused { items: vec![Some(2), None, Some(0)], polls_after_none: &polls, seen_none: false };
+    let mut it = vec![1, 2, 3].into_iter().merge_join_by(right, |l, r| l.cmp(r));
+    assert_eq!(it.by_ref().collect::<Vec<_>>(), vec![Left(1), Both(2, 2), Left(3)]);
+    assert_eq!(it.size_hint(), (0, Some(0)));
+    assert_eq!(it.next(), None);
+    assert_eq!(polls.get(), 0);
+
+    for kind in 0..3 {
+        let polls = Cell::new(0);
+        let left = Unfused { items: vec![Some(1), Some(2), None, Some(2)], polls_after_none: &polls, seen_none: false };
+        let right = vec![2, 2, 3].into_iter();
+        let n = match kind {
+            0 => left.inner_join(right, |l, r| l.cmp(r)).count(),
+            1 => left.left_join(right, |l, r| l.cmp(r)).count(),
+            _ => left.outer_join(right, |l, r| l.cmp(r)).count(),
+        };
+        assert_eq!(n, [2, 3, 4][kind]);
+        assert_eq!(polls.get(), 0);
+    }
+}
+
+#[test]
+fn test_joins_size_hint() {
+    let a = vec![1, 2, 2, 4];
+    let b = vec![0, 2, 2, 2, 3];
+    let key = |l: &i32, r: &i32| l.cmp(r);
+
+    // Check that every size hint, at every step, bounds the number of elements actually left.
+    macro_rules! check_bounds {
+        ($make:expr) => {{
+            let total = $make.count();
+            let mut it = $make;
+            for taken in 0..total + 1 {
+                let (lb, ub) = it.size_hint();
+                let left = total - taken;
+                assert!(lb <= left, "lower bound {} > {} after {}", lb, left, taken);
+                assert!(ub.map_or(true, |ub| ub >= left), "upper bound {:?} < {} after {}", ub, left, taken);
+                it.next();
+            }
+        }};
+    }
+    check_bounds!(a.clone().into_iter().inner_join(b.clone().into_iter(), key));
+    check_bounds!(a.clone().into_iter().left_join(b.clone().into_iter(), key));
+    check_bounds!(a.clone().into_iter().outer_join(b.clone().into_iter(), key));
+
+    assert_eq!(a.clone().into_iter().inner_join(b.clone().into_iter(), key).size_hint(), (0, Some(4 * 5)));
+    assert_eq!(a.clone().into_iter().left_join(b.clone().into_iter(), key).size_hint(), (4, Some(4 * 5)));
+    assert_eq!(a.into_iter().outer_join(b.into_iter(), key).size_hint(), (5, Some(4 * 5 + 5)));
+
+    let (left, right) = vec![1, 2].into_iter().inner_join(vec![2, 3].into_iter(), key).unwrap();
+    assert_eq!((left.0, left.1.collect::<Vec<_>>()), (None, vec![1, 2]));
+    assert_eq!((right.0, right.1.collect::<Vec<_>>()), (None, vec![2, 3]));
+}
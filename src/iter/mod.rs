@@ -28,35 +28,59 @@ The iterator extensions, where possible, should implement the following features
 pub use self::prelude::{
     AccumulateIterator,
     CartesianProductIterator,
+    CheckedSumIterator,
     CloneEachIterator,
+    ExternalSortIterator,
+    FloatSortIterator,
     GroupByIterator,
     FoldlIterator, FoldrIterator,
+    HistogramIterator,
+    IntercalateIterator,
     IntersperseIterator,
     KeepSomeIterator,
+    MergeIterator,
+    MergeJoinIterator,
     PadTailToIterator,
+    QuantileIterator,
     RoundRobinIterator,
+    SetOpIterator,
     SkipExactlyIterator,
     SortedIterator,
     StrideIterator,
     TakeExactlyIterator,
     TeeIterator,
+    ToposortIterator,
     ZipLongestIterator,
 };
 
 pub mod accumulate;
 pub mod cartesian_product;
+pub mod checked_sum;
 pub mod clone_each;
+pub mod external_sort;
+pub mod float_sort;
 pub mod group_by;
 pub mod fold;
+pub mod histogram;
+pub mod intercalate;
 pub mod intersperse;
 pub mod keep_some;
+pub mod merge;
+pub mod merge_join;
+pub mod natural;
 pub mod pad_tail_to;
+pub mod quantile;
+pub mod radix;
 pub mod round_robin;
+pub mod set_ops;
 pub mod skip_exactly;
+pub mod sort_spec;
 pub mod sorted;
 pub mod stride;
+pub mod strided_slice;
 pub mod take_exactly;
 pub mod tee;
+pub mod toposort;
 pub mod zip_longest;
 
 /**
@@ -65,17 +89,27 @@ This module just centralises all the iterator extension traits in one place, sui
 pub mod prelude {
     pub use super::accumulate::AccumulateIterator;
     pub use super::cartesian_product::CartesianProductIterator;
+    pub use super::checked_sum::CheckedSumIterator;
     pub use super::clone_each::CloneEachIterator;
+    pub use super::external_sort::ExternalSortIterator;
+    pub use super::float_sort::FloatSortIterator;
     pub use super::group_by::GroupByIterator;
     pub use super::fold::{FoldlIterator, FoldrIterator};
+    pub use super::histogram::HistogramIterator;
+    pub use super::intercalate::IntercalateIterator;
     pub use super::intersperse::IntersperseIterator;
     pub use super::keep_some::KeepSomeIterator;
+    pub use super::merge::MergeIterator;
+    pub use super::merge_join::MergeJoinIterator;
     pub use super::pad_tail_to::PadTailToIterator;
+    pub use super::quantile::QuantileIterator;
     pub use super::round_robin::RoundRobinIterator;
+    pub use super::set_ops::SetOpIterator;
     pub use super::skip_exactly::SkipExactlyIterator;
     pub use super::sorted::SortedIterator;
     pub use super::stride::StrideIterator;
     pub use super::take_exactly::TakeExactlyIterator;
     pub use super::tee::TeeIterator;
+    pub use super::toposort::ToposortIterator;
     pub use super::zip_longest::ZipLongestIterator;
 }
@@ -0,0 +1,192 @@
+/*
+Copyright ⓒ 2015 grabbag contributors.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+/*!
+Natural ("human") ordering of strings.
+
+In natural order, runs of ASCII digits embedded in a string are compared by their numeric value, rather than character by character.  For example, `"file2"` comes before `"file10"`.
+*/
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/**
+Compares two strings in natural order, using the default options of `NaturalCmp`.
+*/
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    NaturalCmp::new().compare(a, b)
+}
+
+/**
+A configurable natural order comparison.
+
+By default, the comparison is case sensitive, and numbers which differ only in their leading zeros are ordered by the number of leading zeros (fewer first), but only if the strings are otherwise equal.
+
+Comparisons do not allocate.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::SortedIterator;
+# use grabbag::iter::natural::NaturalCmp;
+# fn main() {
+let cmp = NaturalCmp::new().ignore_case(true);
+let v = vec!["File10", "file2", "FILE1"];
+let r: Vec<_> = v.into_iter().sorted_by(|a, b| cmp.compare(a, b)).collect();
+assert_eq!(r, vec!["FILE1", "file2", "File10"]);
+# }
+```
+*/
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct NaturalCmp {
+    ignore_case: bool,
+    ignore_leading_zeros: bool,
+}
+
+impl NaturalCmp {
+    /**
+Creates a case sensitive comparison which distinguishes leading zeros.
+    */
+    pub fn new() -> NaturalCmp {
+        NaturalCmp::default()
+    }
+
+    /**
+Sets whether letters which differ only in case are considered equal.  Case is folded using Unicode lowercase mappings.
+    */
+    pub fn ignore_case(mut self, ignore: bool) -> NaturalCmp {
+        self.ignore_case = ignore;
+        self
+    }
+
+    /**
+Sets whether numbers which differ only in their leading zeros (such as `"007"` and `"7"`) are considered equal.
+    */
+    pub fn ignore_leading_zeros(mut self, ignore: bool) -> NaturalCmp {
+        self.ignore_leading_zeros = ignore;
+        self
+    }
+
+    /**
+Compares two strings in natural order.
+    */
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        let mut a = a.chars().peekable();
+        let mut b = b.chars().peekable();
+
+        // The first difference which is only significant if the strings are otherwise equal.
+        let mut tie_break = Ordering::Equal;
+
+        loop {
+            let (ca, cb) = match (a.peek(), b.peek()) {
+                (None, None) => return tie_break,
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+                (Some(&ca), Some(&cb)) => (ca, cb),
+            };
+
+            if ca.is_ascii_digit() && cb.is_ascii_digit() {
+                let zeros_a = skip_zeros(&mut a);
+                let zeros_b = skip_zeros(&mut b);
+                match compare_digits(&mut a, &mut b) {
+                    Ordering::Equal => (),
+                    ord => return ord
+                }
+                if !self.ignore_leading_zeros && tie_break == Ordering::Equal {
+                    tie_break = zeros_a.cmp(&zeros_b);
+                }
+                continue;
+            }
+
+            a.next();
+            b.next();
+            if ca == cb {
+                continue;
+            }
+            let ord = match self.ignore_case {
+                true => ca.to_lowercase().cmp(cb.to_lowercase()),
+                false => ca.cmp(&cb)
+            };
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        }
+    }
+}
+
+fn skip_zeros(it: &mut Peekable<Chars>) -> usize {
+    let mut n = 0;
+    while it.peek() == Some(&'0') {
+        it.next();
+        n += 1;
+    }
+    n
+}
+
+/*
+Compares two runs of digits without leading zeros, consuming both.  A longer run is a larger number; otherwise, the first differing digit decides.
+*/
+fn compare_digits(a: &mut Peekable<Chars>, b: &mut Peekable<Chars>) -> Ordering {
+    let mut first_diff = Ordering::Equal;
+    loop {
+        let da = a.peek().cloned().filter(|c| c.is_ascii_digit());
+        let db = b.peek().cloned().filter(|c| c.is_ascii_digit());
+        match (da, db) {
+            (None, None) => return first_diff,
+            (None, Some(_)) => {
+                skip_digits(b);
+                return Ordering::Less;
+            },
+            (Some(_), None) => {
+                skip_digits(a);
+                return Ordering::Greater;
+            },
+            (Some(da), Some(db)) => {
+                if first_diff == Ordering::Equal {
+                    first_diff = da.cmp(&db);
+                }
+                a.next();
+                b.next();
+            },
+        }
+    }
+}
+
+fn skip_digits(it: &mut Peekable<Chars>) {
+    while it.peek().map(|c| c.is_ascii_digit()).unwrap_or(false) {
+        it.next();
+    }
+}
+
+#[test]
+fn test_natural_cmp() {
+    assert_eq!(natural_cmp("file2", "file10"), Ordering::Less);
+    assert_eq!(natural_cmp("file10", "file2"), Ordering::Greater);
+    assert_eq!(natural_cmp("file10", "file10"), Ordering::Equal);
+    assert_eq!(natural_cmp("a1b2", "a1b10"), Ordering::Less);
+    assert_eq!(natural_cmp("1.10", "1.9"), Ordering::Greater);
+    assert_eq!(natural_cmp("", "a"), Ordering::Less);
+    assert_eq!(natural_cmp("a", "a1"), Ordering::Less);
+    assert_eq!(natural_cmp("99999999999999999999999", "100000000000000000000000"), Ordering::Less);
+
+    // Leading zeros only matter when everything else is equal.
+    assert_eq!(natural_cmp("a007", "a7"), Ordering::Greater);
+    assert_eq!(natural_cmp("a007b", "a7c"), Ordering::Less);
+    assert_eq!(natural_cmp("a0", "a00"), Ordering::Less);
+    let cmp = NaturalCmp::new().ignore_leading_zeros(true);
+    assert_eq!(cmp.compare("a007", "a7"), Ordering::Equal);
+
+    assert_eq!(natural_cmp("B", "a"), Ordering::Less);
+    let cmp = NaturalCmp::new().ignore_case(true);
+    assert_eq!(cmp.compare("B", "a"), Ordering::Greater);
+    assert_eq!(cmp.compare("ÄRGER2", "ärger10"), Ordering::Less);
+    assert_eq!(cmp.compare("Straße", "STRASSE"), Ordering::Greater);
+    assert_eq!(cmp.compare("ΑΒΓ3", "αβγ3"), Ordering::Equal);
+}
@@ -0,0 +1,310 @@
+/*
+Copyright ⓒ 2015 grabbag contributors.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+/**
+Order statistics of a sequence, without fully sorting it.
+
+The exact methods collect the input and use introselect (quickselect with a guaranteed worst case), which takes linear time on average.  For inputs which are too large to hold in memory, `approx_quantile` estimates a single quantile in constant space.
+*/
+pub trait QuantileIterator<E>: Iterator<Item=E> + Sized {
+    /**
+Returns the `k`th smallest element of the input iterator, counting from zero.
+
+Returns `None` if the iterator has `k` or fewer elements.
+    */
+    fn nth_smallest(self, k: usize) -> Option<E> where E: Ord {
+        let mut v = self.collect::<Vec<_>>();
+        if k >= v.len() {
+            return None;
+        }
+        v.select_nth_unstable(k);
+        Some(v.swap_remove(k))
+    }
+
+    /**
+Returns the median of the input iterator.
+
+If the iterator has an even number of elements, this returns the lower of the two middle elements.  Returns `None` if the iterator is empty.
+    */
+    fn median(self) -> Option<E> where E: Ord {
+        let mut v = self.collect::<Vec<_>>();
+        if v.is_empty() {
+            return None;
+        }
+        let k = (v.len() - 1) / 2;
+        v.select_nth_unstable(k);
+        Some(v.swap_remove(k))
+    }
+
+    /**
+Returns the elements at each of the quantiles `qs`, in the same order as `qs`.
+
+For an input of `n` elements, quantile `q` is the element which would be at index <code>&lfloor;<em>q</em>(<em>n</em> - 1)&rfloor;</code> if the input were sorted.  As such, `0.5` gives the same result as `median`.  Returns `None` if the iterator is empty.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::QuantileIterator;
+# fn main() {
+let v: Vec<u32> = (1..101).rev().collect();
+let r = v.into_iter().quantiles(&[0.5, 0.9, 0.99]);
+assert_eq!(r, Some(vec![50, 90, 99]));
+# }
+```
+
+# Failure
+
+This method will panic if any quantile is not in the range `[0, 1]`.
+    */
+    fn quantiles(self, qs: &[f64]) -> Option<Vec<E>> where E: Ord + Clone {
+        let mut v = self.collect::<Vec<_>>();
+        if v.is_empty() {
+            return None;
+        }
+
+        let max_idx = v.len() - 1;
+        let idxs = qs.iter().map(|&q| {
+            assert!((0.0..=1.0).contains(&q), "quantile {} is not in the range [0, 1]", q);
+            (q * max_idx as f64).floor() as usize
+        }).collect::<Vec<_>>();
+
+        // Select the requested indices in ascending order; each selection partitions the remainder, so later selections only need to look at the elements above the previous one.
+        let mut order = (0..idxs.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| idxs[i]);
+        let mut lo = 0;
+        for &i in &order {
+            let k = idxs[i];
+            if k >= lo {
+                v[lo..].select_nth_unstable(k - lo);
+                lo = k + 1;
+            }
+        }
+
+        Some(idxs.into_iter().map(|k| v[k].clone()).collect())
+    }
+
+    /**
+Estimates quantile `q` of the input iterator using the P² algorithm.
+
+This uses constant space, regardless of the length of the input.  The result is exact for inputs with five or fewer elements.  Returns `None` if the iterator is empty.
+
+See `P2Quantile` for details.
+
+# Failure
+
+This method will panic if `q` is not in the range `[0, 1]`, or if the input contains NaN.
+    */
+    fn approx_quantile(self, q: f64) -> Option<f64> where E: Into<f64> {
+        let mut est = P2Quantile::new(q);
+        for e in self {
+            est.push(e.into());
+        }
+        est.estimate()
+    }
+}
+
+impl<It, E> QuantileIterator<E> for It where It: Iterator<Item=E> {}
+
+/**
+A streaming estimator for a single quantile, using the P² algorithm of Jain and Chlamtac.
+
+The estimator keeps track of five markers: the minimum, the maximum, the target quantile, and the quantiles halfway between the target and either extreme.  As each observation arrives, marker heights are adjusted using piecewise-parabolic interpolation.
+*/
+#[derive(Clone, Debug)]
+pub struct P2Quantile {
+    q: f64,
+    count: usize,
+    heights: [f64; 5],
+    pos: [f64; 5],
+    desired: [f64; 5],
+    incr: [f64; 5],
+}
+
+impl P2Quantile {
+    /**
+Creates an estimator for quantile `q`.
+
+# Failure
+
+This method will panic if `q` is not in the range `[0, 1]`.
+    */
+    pub fn new(q: f64) -> P2Quantile {
+        assert!((0.0..=1.0).contains(&q), "quantile {} is not in the range [0, 1]", q);
+        P2Quantile {
+            q,
+            count: 0,
+            heights: [0.0; 5],
+            pos: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired: [1.0, 1.0 + 2.0*q, 1.0 + 4.0*q, 3.0 + 2.0*q, 5.0],
+            incr: [0.0, q/2.0, q, (1.0 + q)/2.0, 1.0],
+        }
+    }
+
+    /**
+Returns the number of observations seen so far.
+    */
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /**
+Adds an observation to the estimator.
+
+# Failure
+
+This method will panic if `x` is NaN.
+    */
+    pub fn push(&mut self, x: f64) {
+        assert!(!x.is_nan(), "cannot estimate quantiles of NaN");
+        if self.count < 5 {
+            self.heights[self.count] = x;
+            self.count += 1;
+            if self.count == 5 {
+                self.heights.sort_by(|a, b| a.partial_cmp(b).expect("cannot estimate quantiles of NaN"));
+            }
+            return;
+        }
+        self.count += 1;
+
+        // Find the cell containing `x`, extending the extremes if necessary.
+        let h = &mut self.heights;
+        let k = if x < h[0] {
+            h[0] = x;
+            0
+        } else if x >= h[4] {
+            h[4] = x;
+            3
+        } else {
+            (1..5).find(|&i| x < h[i]).expect("NaN sample") - 1
+        };
+
+        for i in (k + 1)..5 {
+            self.pos[i] += 1.0;
+        }
+        for i in 0..5 {
+            self.desired[i] += self.incr[i];
+        }
+
+        // Move the middle markers towards their desired positions.
+        for i in 1..4 {
+            let d = self.desired[i] - self.pos[i];
+            if (d >= 1.0 && self.pos[i+1] - self.pos[i] > 1.0)
+                || (d <= -1.0 && self.pos[i-1] - self.pos[i] < -1.0)
+            {
+                let d = d.signum();
+                let parabolic = self.parabolic(i, d);
+                self.heights[i] = if self.heights[i-1] < parabolic && parabolic < self.heights[i+1] {
+                    parabolic
+                } else {
+                    self.linear(i, d)
+                };
+                self.pos[i] += d;
+            }
+        }
+    }
+
+    /**
+Returns the current estimate of the quantile, or `None` if there have been no observations.
+    */
+    pub fn estimate(&self) -> Option<f64> {
+        match self.count {
+            0 => None,
+            n if n <= 5 => {
+                let mut v = self.heights[..n].to_vec();
+                v.sort_by(|a, b| a.partial_cmp(b).expect("cannot estimate quantiles of NaN"));
+                Some(v[(self.q * (n - 1) as f64).floor() as usize])
+            },
+            _ => Some(self.heights[2])
+        }
+    }
+
+    fn parabolic(&self, i: usize, d: f64) -> f64 {
+        let (h, n) = (&self.heights, &self.pos);
+        h[i] + d / (n[i+1] - n[i-1]) * (
+            (n[i] - n[i-1] + d) * (h[i+1] - h[i]) / (n[i+1] - n[i])
+            + (n[i+1] - n[i] - d) * (h[i] - h[i-1]) / (n[i] - n[i-1])
+        )
+    }
+
+    fn linear(&self, i: usize, d: f64) -> f64 {
+        let (h, n) = (&self.heights, &self.pos);
+        let j = if d < 0.0 { i - 1 } else { i + 1 };
+        h[i] + d * (h[j] - h[i]) / (n[j] - n[i])
+    }
+}
+
+#[test]
+fn test_nth_smallest() {
+    let v = vec![5usize, 1, 4, 2, 3, 0];
+    assert_eq!(v.clone().into_iter().nth_smallest(0), Some(0));
+    assert_eq!(v.clone().into_iter().nth_smallest(3), Some(3));
+    assert_eq!(v.clone().into_iter().nth_smallest(5), Some(5));
+    assert_eq!(v.into_iter().nth_smallest(6), None);
+}
+
+#[test]
+fn test_median() {
+    let v: Vec<i32> = vec![];
+    assert_eq!(v.into_iter().median(), None);
+
+    let v = vec![3i32, 1, 2];
+    assert_eq!(v.into_iter().median(), Some(2));
+
+    let v = vec![4i32, 1, 3, 2];
+    assert_eq!(v.into_iter().median(), Some(2));
+}
+
+#[test]
+fn test_quantiles() {
+    let v: Vec<u32> = vec![];
+    assert_eq!(v.into_iter().quantiles(&[0.5]), None);
+
+    let v: Vec<u32> = (0..11).rev().collect();
+    let r = v.into_iter().quantiles(&[1.0, 0.0, 0.5, 0.25, 0.5]);
+    assert_eq!(r, Some(vec![10, 0, 5, 2, 5]));
+}
+
+#[test]
+#[should_panic(expected = "cannot estimate quantiles of NaN")]
+fn test_approx_quantile_nan() {
+    // Past the first five samples, where the markers are already sorted.
+    let mut p2 = P2Quantile::new(0.5);
+    for i in 0..10 {
+        p2.push(i as f64);
+    }
+    p2.push(f64::NAN);
+}
+
+#[test]
+#[should_panic(expected = "cannot estimate quantiles of NaN")]
+fn test_approx_quantile_nan_first() {
+    let _ = vec![1.0, f64::NAN, 2.0].into_iter().approx_quantile(0.5);
+}
+
+#[test]
+fn test_approx_quantile() {
+    // A simple LCG, so that the input isn't in any particular order.
+    let mut seed = 12345u32;
+    let v: Vec<f64> = (0..10000).map(|_| {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        ((seed >> 8) % 10000) as f64
+    }).collect();
+
+    for &q in &[0.1, 0.5, 0.9, 0.99] {
+        let est = v.iter().cloned().approx_quantile(q).unwrap();
+        assert!((est - q * 10000.0).abs() < 150.0, "q = {}, est = {}", q, est);
+    }
+
+    let v = vec![3.0f64, 1.0, 2.0];
+    assert_eq!(v.into_iter().approx_quantile(0.5), Some(2.0));
+
+    let v: Vec<f64> = vec![];
+    assert_eq!(v.into_iter().approx_quantile(0.5), None);
+}
@@ -0,0 +1,305 @@
+/*
+Copyright ⓒ 2015 grabbag contributors.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+/*!
+Radix sorting of fixed-width and byte-string keys.
+
+Fixed-width keys are sorted with a least-significant-digit radix sort, one byte per pass; passes in which every key has the same byte are skipped.  Byte-string keys are sorted with a most-significant-digit radix sort, which only examines as much of each key as is needed to tell it apart from its neighbours.
+
+Both sorts are stable, and produce exactly the same order as the equivalent comparison sort.  Small inputs fall back to a comparison sort.
+
+Radix sorting is not always faster.  It does best on narrow keys: in `benches/radix.rs`, sorting 10,000 `u32`s takes well under half the time of `sorted`, but 64-bit keys only break even at that size, and with a million elements every key width is slower than `sorted_unstable`.  The scatter passes are memory-bound, so results vary between machines; run `cargo bench --bench radix` to compare.
+*/
+use std::mem;
+
+/*
+Below this many elements, a comparison sort is used instead.
+*/
+const SMALL: usize = 64;
+
+/**
+Types which can be used as fixed-width radix sort keys.
+
+A key is mapped to an unsigned integer whose natural order matches the order of the key.  Signed integers have their sign bit flipped; floating point values are ordered using the IEEE 754 `totalOrder` predicate, as with `FloatSortIterator::sorted_floats`.
+*/
+pub trait RadixKey: Copy {
+    /**
+The number of significant bytes in the result of `to_radix`.
+    */
+    const BYTES: usize;
+
+    /**
+Maps this value to an unsigned integer with the same ordering.
+    */
+    fn to_radix(self) -> u64;
+}
+
+macro_rules! impl_radix_key_unsigned {
+    ($($ty:ty),*) => {
+        $(
+            impl RadixKey for $ty {
+                const BYTES: usize = mem::size_of::<$ty>();
+                fn to_radix(self) -> u64 { self as u64 }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_radix_key_signed {
+    ($($ty:ty => $uty:ty),*) => {
+        $(
+            impl RadixKey for $ty {
+                const BYTES: usize = mem::size_of::<$ty>();
+                fn to_radix(self) -> u64 {
+                    ((self as $uty) ^ (1 << (mem::size_of::<$ty>() * 8 - 1))) as u64
+                }
+            }
+        )*
+    };
+}
+
+impl_radix_key_unsigned! { u8, u16, u32, u64, usize, char, bool }
+impl_radix_key_signed! { i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize }
+
+impl RadixKey for f32 {
+    const BYTES: usize = 4;
+    fn to_radix(self) -> u64 {
+        let bits = self.to_bits();
+        u64::from(match bits >> 31 {
+            0 => bits ^ (1 << 31),
+            _ => !bits,
+        })
+    }
+}
+
+impl RadixKey for f64 {
+    const BYTES: usize = 8;
+    fn to_radix(self) -> u64 {
+        let bits = self.to_bits();
+        match bits >> 63 {
+            0 => bits ^ (1 << 63),
+            _ => !bits,
+        }
+    }
+}
+
+/**
+Sorts `v` by the fixed-width key computed by `key`.
+
+`key` is called exactly once per element.  The sort is stable, and requires <em>O</em>(<em>n</em>) additional memory.
+*/
+pub fn sort_by_key<E, K, F>(v: Vec<E>, mut key: F) -> Vec<E> where K: RadixKey, F: FnMut(&E) -> K {
+    let mut keys = v.iter().enumerate()
+        .map(|(i, e)| (key(e).to_radix(), i))
+        .collect::<Vec<_>>();
+    lsd(&mut keys, K::BYTES, |&(k, _)| k);
+    permute(v, keys.into_iter().map(|(_, i)| i))
+}
+
+/**
+Sorts `v`, whose elements are their own keys.
+
+This is equivalent to `sort_by_key(v, |&e| e)`, but avoids the need to track where each element came from.
+*/
+pub fn sort<E>(mut v: Vec<E>) -> Vec<E> where E: RadixKey {
+    lsd(&mut v, E::BYTES, |e| e.to_radix());
+    v
+}
+
+/**
+Sorts `v` by the byte string returned by `key`, in lexicographic order.
+
+Strings compare the same way as their UTF-8 encodings, so this can also be used for `str` keys.  The sort is stable.
+*/
+pub fn sort_bytes_by_key<E, F>(v: Vec<E>, key: F) -> Vec<E> where F: FnMut(&E) -> &[u8] {
+    let idx = {
+        let keys = v.iter().map(key).collect::<Vec<_>>();
+        let mut idx = (0..keys.len()).collect::<Vec<_>>();
+        msd(&keys, &mut idx);
+        idx
+    };
+    permute(v, idx.into_iter())
+}
+
+/*
+Stable LSD radix sort, one byte of the key per pass.
+*/
+fn lsd<T, F>(v: &mut Vec<T>, bytes: usize, key: F) where T: Copy, F: Fn(&T) -> u64 {
+    let n = v.len();
+    if n <= SMALL {
+        v.sort_by_key(|e| key(e));
+        return;
+    }
+
+    // Count every digit in a single pass over the input.
+    let mut counts = vec![[0usize; 256]; bytes];
+    for e in v.iter() {
+        let k = key(e);
+        for (pass, c) in counts.iter_mut().enumerate() {
+            c[(k >> (pass * 8)) as u8 as usize] += 1;
+        }
+    }
+
+    let mut scratch = v.clone();
+    for (pass, counts) in counts.iter().enumerate() {
+        if counts.contains(&n) {
+            continue;
+        }
+
+        let mut pos = [0usize; 256];
+        let mut sum = 0;
+        for (p, &c) in pos.iter_mut().zip(counts.iter()) {
+            *p = sum;
+            sum += c;
+        }
+        let shift = pass * 8;
+        for e in v.iter() {
+            let d = (key(e) >> shift) as u8 as usize;
+            scratch[pos[d]] = *e;
+            pos[d] += 1;
+        }
+        mem::swap(v, &mut scratch);
+    }
+}
+
+/*
+Stable MSD radix sort of the indices `idx` into `keys`.
+
+Bucket 0 holds keys which have ended; bucket `b + 1` holds keys whose next byte is `b`.  Ranges are processed from an explicit stack, so long common prefixes cannot overflow the call stack.
+*/
+fn msd(keys: &[&[u8]], idx: &mut [usize]) {
+    let bucket = |i: usize, depth: usize| keys[i].get(depth).map(|&b| b as usize + 1).unwrap_or(0);
+
+    let mut scratch = vec![0; idx.len()];
+    let mut stack = vec![(0, idx.len(), 0)];
+    while let Some((start, end, depth)) = stack.pop() {
+        let range = &mut idx[start..end];
+        if range.len() <= SMALL {
+            range.sort_by(|&a, &b| keys[a][depth..].cmp(&keys[b][depth..]));
+            continue;
+        }
+
+        let mut counts = [0usize; 257];
+        for &i in range.iter() {
+            counts[bucket(i, depth)] += 1;
+        }
+
+        let mut starts = [0usize; 257];
+        let mut sum = 0;
+        for (s, &c) in starts.iter_mut().zip(counts.iter()) {
+            *s = sum;
+            sum += c;
+        }
+
+        if counts[1..].iter().all(|&c| c < range.len()) {
+            let mut pos = starts;
+            for &i in range.iter() {
+                let b = bucket(i, depth);
+                scratch[pos[b]] = i;
+                pos[b] += 1;
+            }
+            range.copy_from_slice(&scratch[..range.len()]);
+        }
+
+        // Keys in bucket 0 have ended, and are therefore all equal.
+        for b in 1..257 {
+            if counts[b] > 1 {
+                stack.push((start + starts[b], start + starts[b] + counts[b], depth + 1));
+            }
+        }
+    }
+}
+
+/*
+Returns the elements of `v` in the order given by `idx`, which must be a permutation of its indices.
+*/
+fn permute<E, I>(v: Vec<E>, idx: I) -> Vec<E> where I: Iterator<Item=usize> {
+    let mut v = v.into_iter().map(Some).collect::<Vec<_>>();
+    idx.map(|i| v[i].take().expect("index appeared twice in permutation")).collect()
+}
+
+#[cfg(test)]
+fn xorshift(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+#[test]
+fn test_sort() {
+    let mut state = 0x2545_f491_4f6c_dd1d;
+    for &n in &[0, 1, 5, SMALL, SMALL + 1, 1000, 5000] {
+        let v: Vec<u32> = (0..n).map(|_| xorshift(&mut state) as u32).collect();
+        let mut expected = v.clone();
+        expected.sort();
+        assert_eq!(sort(v), expected);
+
+        let v: Vec<i64> = (0..n).map(|_| xorshift(&mut state) as i64 >> (n % 40)).collect();
+        let mut expected = v.clone();
+        expected.sort();
+        assert_eq!(sort(v), expected);
+
+        let v: Vec<i8> = (0..n).map(|_| xorshift(&mut state) as i8).collect();
+        let mut expected = v.clone();
+        expected.sort();
+        assert_eq!(sort(v), expected);
+    }
+}
+
+#[test]
+fn test_sort_floats() {
+    let mut state = 0x9e37_79b9_7f4a_7c15;
+    let mut v: Vec<f64> = (0..500).map(|_| (xorshift(&mut state) as i64) as f64 / 1e9).collect();
+    v.extend(vec![0.0, -0.0, f64::INFINITY, f64::NEG_INFINITY, f64::NAN, -f64::NAN]);
+    let mut expected = v.clone();
+    expected.sort_by(|a, b| a.total_cmp(b));
+    let r = sort(v);
+    assert_eq!(
+        r.iter().map(|e| e.to_bits()).collect::<Vec<_>>(),
+        expected.iter().map(|e| e.to_bits()).collect::<Vec<_>>());
+
+    let v = vec![1.5f32, -2.0, 0.0, -0.0, f32::NAN];
+    let r = sort(v);
+    assert_eq!(&r[..4], &[-2.0, -0.0, 0.0, 1.5]);
+    assert!(r[4].is_nan());
+}
+
+#[test]
+fn test_sort_by_key_stable() {
+    let mut state = 0x1234_5678_9abc_def1;
+    let v: Vec<(u8, usize)> = (0..3000).map(|i| (xorshift(&mut state) as u8 % 7, i)).collect();
+    let mut expected = v.clone();
+    expected.sort_by_key(|e| e.0);
+    assert_eq!(sort_by_key(v, |e| e.0), expected);
+}
+
+#[test]
+fn test_sort_bytes_by_key() {
+    let mut state = 0x0f0f_1e1e_2d2d_3c3c;
+    for &n in &[0, 3, SMALL + 1, 2000] {
+        let v: Vec<(String, usize)> = (0..n)
+            .map(|i| {
+                let len = xorshift(&mut state) % 6;
+                let s: String = (0..len).map(|_| (b'a' + (xorshift(&mut state) % 3) as u8) as char).collect();
+                (s, i)
+            })
+            .collect();
+        let mut expected = v.clone();
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(sort_bytes_by_key(v, |e| e.0.as_bytes()), expected);
+    }
+
+    // Long common prefixes.
+    let long = "x".repeat(10_000);
+    let v: Vec<String> = (0..200).map(|i| format!("{}{}", long, i % 10)).collect();
+    let mut expected = v.clone();
+    expected.sort();
+    assert_eq!(sort_bytes_by_key(v, |e| e.as_bytes()), expected);
+}
@@ -83,13 +83,52 @@ impl<It0, It1, E> Iterator for RoundRobin<It0, It1> where It0: Iterator<Item=E>,
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        match (self.it0.size_hint(), self.it1.size_hint()) {
-            ((l0, None), (l1, _)) | ((l0, _), (l1, None)) => (l0+l1, None),
-            ((l0, Some(u0)), (l1, Some(u1))) => (l0+l1, Some(2*min(u0, u1)))
+        let ((l0, u0), (l1, u1)) = (self.it0.size_hint(), self.it1.size_hint());
+        let lb = self.remaining(Some(l0), Some(l1)).unwrap_or(usize::MAX);
+        (lb, self.remaining(u0, u1))
+    }
+}
+
+impl<It0, It1> RoundRobin<It0, It1> {
+    /*
+Returns how many elements will be yielded if the underlying iterators have `n0` and `n1` elements left, where `None` means unbounded.  The iterator whose turn it is fails after its `n`th element, so it stops after `2n` elements; the other stops after `2n + 1`.
+    */
+    fn remaining(&self, n0: Option<usize>, n1: Option<usize>) -> Option<usize> {
+        let (first, second) = match self.phase {
+            0 => (n0, n1),
+            _ => (n1, n0)
+        };
+        let first = first.map(|n| n.saturating_mul(2));
+        let second = second.map(|n| n.saturating_mul(2).saturating_add(1));
+        match (first, second) {
+            (Some(a), Some(b)) => Some(min(a, b)),
+            (a, None) => a,
+            (None, b) => b
+        }
+    }
+}
+
+impl<It0, It1, E> DoubleEndedIterator for RoundRobin<It0, It1>
+where It0: DoubleEndedIterator<Item=E> + ExactSizeIterator, It1: DoubleEndedIterator<Item=E> + ExactSizeIterator {
+    fn next_back(&mut self) -> Option<E> {
+        let (n0, n1) = (self.it0.len(), self.it1.len());
+        let n = self.remaining(Some(n0), Some(n1)).unwrap();
+        if n == 0 {
+            return None;
+        }
+
+        // Find which iterator the last element comes from, and drop anything after it which the front would never reach.
+        let last = n - 1;
+        match (self.phase as usize + last) % 2 {
+            0 => self.it0.nth_back(n0 - 1 - last / 2),
+            _ => self.it1.nth_back(n1 - 1 - last / 2)
         }
     }
 }
 
+impl<It0, It1, E> ExactSizeIterator for RoundRobin<It0, It1>
+where It0: ExactSizeIterator<Item=E>, It1: ExactSizeIterator<Item=E> {}
+
 #[derive(Clone, Debug)]
 pub struct RoundRobinLongest<It0, It1> {
     it0: It0,
@@ -147,11 +186,515 @@ impl<It0, It1, E> Iterator for RoundRobinLongest<It0, It1> where It0: Iterator<I
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        match (self.it0.size_hint(), self.it1.size_hint()) {
-            ((l0, None), (l1, _)) | ((l0, _), (l1, None)) => (l0+l1, None),
-            ((l0, Some(u0)), (l1, Some(u1))) => (l0+l1, Some(u0+u1))
+        let ((l0, u0), (l1, u1)) = (self.it0.size_hint(), self.it1.size_hint());
+        let u = match (u0, u1) {
+            (Some(u0), Some(u1)) => u0.checked_add(u1),
+            _ => None
+        };
+        (l0.saturating_add(l1), u)
+    }
+}
+
+impl<It0, It1, E> DoubleEndedIterator for RoundRobinLongest<It0, It1>
+where It0: DoubleEndedIterator<Item=E> + ExactSizeIterator, It1: DoubleEndedIterator<Item=E> + ExactSizeIterator {
+    fn next_back(&mut self) -> Option<E> {
+        // Once one iterator runs out, the rest of the output comes from the other.  The iterator whose turn it is runs out first unless it is strictly longer.
+        let (n0, n1) = (self.it0.len(), self.it1.len());
+        let from_it0 = match (self.phase, self.fused) {
+            (0, true) => true,
+            (_, true) => false,
+            (0, false) => n0 > n1,
+            (_, false) => n0 >= n1,
+        };
+        match from_it0 {
+            true => self.it0.next_back(),
+            false => self.it1.next_back()
+        }
+    }
+}
+
+impl<It0, It1, E> ExactSizeIterator for RoundRobinLongest<It0, It1>
+where It0: ExactSizeIterator<Item=E>, It1: ExactSizeIterator<Item=E> {}
+
+/**
+Creates an iterator that cycles between yielding elements of each of the input iterators in turn.  It stops as soon as any iterator is exhausted.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::round_robin::round_robin_all;
+# fn main() {
+let its = vec![vec![0, 3, 6], vec![1, 4], vec![2, 5, 8]];
+let r: Vec<_> = round_robin_all(its).collect();
+assert_eq!(r, vec![0, 1, 2, 3, 4, 5, 6]);
+# }
+```
+*/
+pub fn round_robin_all<Its>(iters: Its) -> RoundRobinAll<<Its::Item as IntoIterator>::IntoIter>
+where Its: IntoIterator, Its::Item: IntoIterator {
+    RoundRobinAll {
+        iters: iters.into_iter().map(IntoIterator::into_iter).collect(),
+        pos: 0,
+        done: false,
+    }
+}
+
+/**
+Creates an iterator that cycles between yielding elements of each of the input iterators in turn.  Iterators which are exhausted are simply skipped.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::round_robin::round_robin_all_longest;
+# fn main() {
+let its = vec![vec![0, 3, 6], vec![1, 4], vec![2, 5, 8, 9]];
+let r: Vec<_> = round_robin_all_longest(its).collect();
+assert_eq!(r, vec![0, 1, 2, 3, 4, 5, 6, 8, 9]);
+# }
+```
+*/
+pub fn round_robin_all_longest<Its>(iters: Its) -> RoundRobinAllLongest<<Its::Item as IntoIterator>::IntoIter>
+where Its: IntoIterator, Its::Item: IntoIterator {
+    RoundRobinAllLongest {
+        iters: iters.into_iter().map(IntoIterator::into_iter).collect(),
+        pos: 0,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RoundRobinAll<It> {
+    iters: Vec<It>,
+    pos: usize,
+    done: bool,
+}
+
+impl<It> RoundRobinAll<It> {
+    /**
+Unwraps the iterator, returning the underlying iterators in their original order.
+    */
+    pub fn unwrap(self) -> Vec<It> {
+        self.iters
+    }
+}
+
+impl<It, E> Iterator for RoundRobinAll<It> where It: Iterator<Item=E> {
+    type Item = E;
+
+    fn next(&mut self) -> Option<E> {
+        if self.done || self.iters.is_empty() {
+            return None;
+        }
+        match self.iters[self.pos].next() {
+            None => {
+                self.done = true;
+                None
+            },
+            e => {
+                self.pos = (self.pos + 1) % self.iters.len();
+                e
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done || self.iters.is_empty() {
+            return (0, Some(0));
+        }
+
+        // The iterator `t` places after the current one fails on its `n + 1`th call, where `n` is its length; that is, after `n * k + t` elements.
+        let k = self.iters.len();
+        let polled = |t: usize, n: usize| n.saturating_mul(k).saturating_add(t);
+        let mut lb = usize::MAX;
+        let mut ub = None;
+        for t in 0..k {
+            let (l, u) = self.iters[(self.pos + t) % k].size_hint();
+            lb = min(lb, polled(t, l));
+            if let Some(u) = u {
+                ub = Some(min(ub.unwrap_or(usize::MAX), polled(t, u)));
+            }
+        }
+        (lb, ub)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RoundRobinAllLongest<It> {
+    iters: Vec<It>,
+    pos: usize,
+}
+
+impl<It> RoundRobinAllLongest<It> {
+    /**
+Unwraps the iterator, returning the underlying iterators which have not yet been found to be exhausted, in their original order.
+    */
+    pub fn unwrap(self) -> Vec<It> {
+        self.iters
+    }
+}
+
+impl<It, E> Iterator for RoundRobinAllLongest<It> where It: Iterator<Item=E> {
+    type Item = E;
+
+    fn next(&mut self) -> Option<E> {
+        while !self.iters.is_empty() {
+            match self.iters[self.pos].next() {
+                None => {
+                    self.iters.remove(self.pos);
+                    if self.pos == self.iters.len() {
+                        self.pos = 0;
+                    }
+                },
+                e => {
+                    self.pos = (self.pos + 1) % self.iters.len();
+                    return e;
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iters.iter()
+            .map(|it| it.size_hint())
+            .fold((0, Some(0)), |(l0, u0), (l1, u1)| {
+                (l0.saturating_add(l1), u0.and_then(|u0| u1.and_then(|u1| u0.checked_add(u1))))
+            })
+    }
+}
+
+/**
+Creates an iterator that interleaves elements from several input iterators in proportion to their weights, using smooth weighted round robin.
+
+Over any stretch of output, each iterator's share of the elements stays close to its share of the total weight, and elements from heavier iterators are spread out rather than yielded in bursts.  Where iterators are otherwise tied, the one which appears first in `sources` goes first.
+
+By default, an exhausted iterator is dropped and the rest continue with their own weights; use `RoundRobinWeighted::on_exhausted` to change this.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::round_robin::round_robin_weighted;
+# fn main() {
+let high = vec!["h0", "h1", "h2", "h3", "h4", "h5"];
+let low = vec!["l0", "l1", "l2"];
+let r: Vec<_> = round_robin_weighted(vec![(high.into_iter(), 3), (low.into_iter(), 1)]).collect();
+assert_eq!(r, vec!["h0", "h1", "l0", "h2", "h3", "h4", "l1", "h5", "l2"]);
+# }
+```
+
+# Failure
+
+This function will panic if any weight is zero.
+*/
+pub fn round_robin_weighted<Its, It>(sources: Its) -> RoundRobinWeighted<It::IntoIter>
+where Its: IntoIterator<Item=(It, usize)>, It: IntoIterator {
+    let sources = sources.into_iter()
+        .map(|(it, weight)| {
+            assert!(weight != 0, "round robin weights must be non-zero");
+            WeightedSource {
+                iter: it.into_iter(),
+                weight,
+                current: 0,
+            }
+        })
+        .collect::<Vec<_>>();
+    RoundRobinWeighted {
+        total: sources.iter().map(|s| s.weight).sum(),
+        sources,
+        policy: WeightPolicy::Redistribute,
+        done: false,
+    }
+}
+
+/**
+What `RoundRobinWeighted` does when one of its input iterators is exhausted.
+*/
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum WeightPolicy {
+    /// Stop as soon as any input iterator is exhausted.
+    Stop,
+    /// Drop the exhausted iterator.  The others share its weight in proportion to their own, and the schedule carries on from where it was.
+    Redistribute,
+    /// Drop the exhausted iterator, and restart the schedule from scratch among the others.
+    Reset,
+}
+
+#[derive(Clone, Debug)]
+struct WeightedSource<It> {
+    iter: It,
+    weight: usize,
+    current: isize,
+}
+
+#[derive(Clone, Debug)]
+pub struct RoundRobinWeighted<It> {
+    sources: Vec<WeightedSource<It>>,
+    total: usize,
+    policy: WeightPolicy,
+    done: bool,
+}
+
+impl<It> RoundRobinWeighted<It> {
+    /**
+Sets what happens when an input iterator is exhausted.  The default is `WeightPolicy::Redistribute`.
+    */
+    pub fn on_exhausted(mut self, policy: WeightPolicy) -> RoundRobinWeighted<It> {
+        self.policy = policy;
+        self
+    }
+
+    /**
+Unwraps the iterator, returning the underlying iterators and their weights, in their original order.  Iterators which have been dropped because they were exhausted are not included.
+    */
+    pub fn unwrap(self) -> Vec<(It, usize)> {
+        self.sources.into_iter().map(|s| (s.iter, s.weight)).collect()
+    }
+}
+
+impl<It, E> Iterator for RoundRobinWeighted<It> where It: Iterator<Item=E> {
+    type Item = E;
+
+    fn next(&mut self) -> Option<E> {
+        while !self.done && !self.sources.is_empty() {
+            // Every source gains its weight, then the one furthest ahead pays for its turn with the total.
+            for s in &mut self.sources {
+                s.current += s.weight as isize;
+            }
+            let mut pick = 0;
+            for i in 1..self.sources.len() {
+                if self.sources[i].current > self.sources[pick].current {
+                    pick = i;
+                }
+            }
+            self.sources[pick].current -= self.total as isize;
+
+            if let Some(e) = self.sources[pick].iter.next() {
+                return Some(e);
+            }
+
+            match self.policy {
+                WeightPolicy::Stop => self.done = true,
+                WeightPolicy::Redistribute => {
+                    // Undo this round, as though the exhausted source had already been dropped.
+                    self.sources[pick].current += self.total as isize;
+                    for s in &mut self.sources {
+                        s.current -= s.weight as isize;
+                    }
+                    let s = self.sources.remove(pick);
+                    self.total -= s.weight;
+                },
+                WeightPolicy::Reset => {
+                    let s = self.sources.remove(pick);
+                    self.total -= s.weight;
+                    for s in &mut self.sources {
+                        s.current = 0;
+                    }
+                },
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            return (0, Some(0));
+        }
+        let hints = self.sources.iter().map(|s| s.iter.size_hint());
+        let ub = hints.clone().try_fold(0usize, |a, (_, u)| u.and_then(|u| a.checked_add(u)));
+        let lb = match self.policy {
+            // Every element of the first iterator to run out is yielded before it does.
+            WeightPolicy::Stop => hints.map(|(l, _)| l).min().unwrap_or(0),
+            _ => hints.fold(0usize, |a, (l, _)| a.saturating_add(l))
+        };
+        (lb, ub)
+    }
+}
+
+/**
+A round robin over a changing set of iterators.
+
+Iterators can be pushed into, and removed from, the pool at any time, including between calls to `next`.  Each pushed iterator is identified by a `PoolHandle`, and every element yielded by the pool is tagged with the handle of the iterator it came from.  When an iterator is exhausted, it is removed from the pool and reported with a `PoolEvent::Exhausted` event.
+
+Once the pool is empty, `next` returns `None`; pushing another iterator into the pool will cause it to resume.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::round_robin::{PoolEvent, RoundRobinPool};
+# fn main() {
+let mut pool = RoundRobinPool::new();
+let a = pool.push(vec!["a0", "a1"]);
+let b = pool.push(vec!["b0"]);
+assert_eq!(pool.next(), Some(PoolEvent::Item(a, "a0")));
+let c = pool.push(vec!["c0"]);
+assert_eq!(pool.next(), Some(PoolEvent::Item(b, "b0")));
+assert_eq!(pool.next(), Some(PoolEvent::Item(a, "a1")));
+assert_eq!(pool.next(), Some(PoolEvent::Item(c, "c0")));
+assert_eq!(pool.next(), Some(PoolEvent::Exhausted(b)));
+assert!(pool.remove(c).is_some());
+assert_eq!(pool.next(), Some(PoolEvent::Exhausted(a)));
+assert_eq!(pool.next(), None);
+# }
+```
+*/
+#[derive(Clone, Debug)]
+pub struct RoundRobinPool<It> {
+    sources: Vec<(PoolHandle, It)>,
+    pos: usize,
+    next_handle: usize,
+}
+
+/**
+Identifies an iterator within a `RoundRobinPool`.
+
+Handles are never reused by the pool which issued them.
+*/
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct PoolHandle(usize);
+
+/**
+An event produced by a `RoundRobinPool`.
+*/
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PoolEvent<E> {
+    /// An element yielded by the iterator with the given handle.
+    Item(PoolHandle, E),
+    /// The iterator with the given handle was exhausted, and has been removed from the pool.
+    Exhausted(PoolHandle),
+}
+
+impl<E> PoolEvent<E> {
+    /**
+Returns the handle of the iterator this event relates to.
+    */
+    pub fn handle(&self) -> PoolHandle {
+        match *self {
+            PoolEvent::Item(h, _) | PoolEvent::Exhausted(h) => h
+        }
+    }
+
+    /**
+Returns the tagged element, if this event is an `Item`.
+    */
+    pub fn into_item(self) -> Option<(PoolHandle, E)> {
+        match self {
+            PoolEvent::Item(h, e) => Some((h, e)),
+            PoolEvent::Exhausted(_) => None
+        }
+    }
+}
+
+impl<It> RoundRobinPool<It> {
+    /**
+Creates an empty pool.
+    */
+    pub fn new() -> RoundRobinPool<It> {
+        RoundRobinPool {
+            sources: vec![],
+            pos: 0,
+            next_handle: 0,
         }
     }
+
+    /**
+Adds an iterator to the pool, returning its handle.  It takes its first turn after every iterator already in the pool has had one.
+    */
+    pub fn push<I>(&mut self, iter: I) -> PoolHandle where I: IntoIterator<IntoIter=It> {
+        let handle = PoolHandle(self.next_handle);
+        self.next_handle += 1;
+        // Insert just before the current position, so every other iterator gets a turn first.
+        self.sources.insert(self.pos, (handle, iter.into_iter()));
+        self.pos += 1;
+        if self.pos == self.sources.len() {
+            self.pos = 0;
+        }
+        handle
+    }
+
+    /**
+Removes an iterator from the pool, returning it.  Returns `None` if the iterator has already been removed, or was exhausted.
+    */
+    pub fn remove(&mut self, handle: PoolHandle) -> Option<It> {
+        let i = self.sources.iter().position(|&(h, _)| h == handle)?;
+        let (_, it) = self.sources.remove(i);
+        if i < self.pos {
+            self.pos -= 1;
+        }
+        if self.pos == self.sources.len() {
+            self.pos = 0;
+        }
+        Some(it)
+    }
+
+    /**
+Returns `true` if the iterator with the given handle is still in the pool.
+    */
+    pub fn contains(&self, handle: PoolHandle) -> bool {
+        self.sources.iter().any(|&(h, _)| h == handle)
+    }
+
+    /**
+Returns the number of iterators in the pool.
+    */
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    /**
+Returns `true` if there are no iterators in the pool.
+    */
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /**
+Unwraps the pool, returning the iterators remaining in it, and their handles.
+    */
+    pub fn unwrap(self) -> Vec<(PoolHandle, It)> {
+        self.sources
+    }
+}
+
+impl<It> Default for RoundRobinPool<It> {
+    fn default() -> RoundRobinPool<It> {
+        RoundRobinPool::new()
+    }
+}
+
+impl<It, E> Iterator for RoundRobinPool<It> where It: Iterator<Item=E> {
+    type Item = PoolEvent<E>;
+
+    fn next(&mut self) -> Option<PoolEvent<E>> {
+        if self.sources.is_empty() {
+            return None;
+        }
+        let handle = self.sources[self.pos].0;
+        match self.sources[self.pos].1.next() {
+            Some(e) => {
+                self.pos = (self.pos + 1) % self.sources.len();
+                Some(PoolEvent::Item(handle, e))
+            },
+            None => {
+                self.remove(handle);
+                Some(PoolEvent::Exhausted(handle))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every iterator with an upper bound will also produce an `Exhausted` event.
+        self.sources.iter()
+            .map(|(_, it)| match it.size_hint() {
+                (l, Some(u)) => (l.saturating_add(1), u.checked_add(1)),
+                (l, None) => (l, None)
+            })
+            .fold((0, Some(0)), |(l0, u0), (l1, u1)| {
+                (l0.saturating_add(l1), u0.and_then(|u0| u1.and_then(|u1| u0.checked_add(u1))))
+            })
+    }
 }
 
 #[test]
@@ -182,3 +725,195 @@ fn test_round_robin_longest() {
     assert_eq!(it.next(), Some(7));
     assert_eq!(it.next(), None);
 }
+
+#[test]
+fn test_round_robin_all() {
+    let its = vec![vec![0usize, 3, 6, 9], vec![1, 4, 7], vec![2, 5, 8, 11]];
+    let mut it = round_robin_all(its.clone());
+    assert_eq!(it.size_hint(), (10, Some(10)));
+    assert_eq!(it.next(), Some(0));
+    assert_eq!(it.size_hint(), (9, Some(9)));
+    assert_eq!(it.by_ref().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    assert_eq!(it.size_hint(), (0, Some(0)));
+    assert_eq!(it.next(), None);
+    let rest: Vec<Vec<_>> = it.unwrap().into_iter().map(|it| it.collect()).collect();
+    assert_eq!(rest, vec![vec![], vec![], vec![11]]);
+
+    let mut it = round_robin_all(vec![0..2, 10..12].into_iter().chain(Some(20..20)));
+    assert_eq!(it.size_hint(), (2, Some(2)));
+    assert_eq!(it.next(), Some(0));
+    assert_eq!(it.next(), Some(10));
+    assert_eq!(it.next(), None);
+
+    let its: Vec<Vec<i32>> = vec![];
+    assert_eq!(round_robin_all(its).next(), None);
+}
+
+#[test]
+fn test_round_robin_all_longest() {
+    let its = vec![vec![0usize, 3], vec![], vec![1, 4, 6, 7], vec![2, 5]];
+    let mut it = round_robin_all_longest(its);
+    assert_eq!(it.size_hint(), (8, Some(8)));
+    assert_eq!(it.by_ref().take(3).collect::<Vec<_>>(), vec![0, 1, 2]);
+    assert_eq!(it.size_hint(), (5, Some(5)));
+    assert_eq!(it.unwrap().len(), 3);
+
+    let its = vec![vec![0usize, 3], vec![], vec![1, 4, 6, 7], vec![2, 5]];
+    let r: Vec<_> = round_robin_all_longest(its).collect();
+    assert_eq!(r, vec![0, 1, 2, 3, 4, 5, 6, 7]);
+
+    let its: Vec<Vec<i32>> = vec![vec![], vec![]];
+    let mut it = round_robin_all_longest(its);
+    assert_eq!(it.next(), None);
+    assert!(it.unwrap().is_empty());
+}
+
+#[test]
+fn test_round_robin_weighted() {
+    let r: String = round_robin_weighted(vec![("aaaaaaaaaaaa".chars(), 5), ("bbbbbbbbbbbb".chars(), 1), ("cccccccccccc".chars(), 1)])
+        .take(14).collect();
+    assert_eq!(r, "aabacaaaabacaa");
+
+    // Redistribute: once `b` runs out, `a` and `c` share the output 2:1.
+    let it = round_robin_weighted(vec![(0..100, 2), (100..101, 1), (200..300, 1)]);
+    assert_eq!(it.size_hint(), (201, Some(201)));
+    let r: Vec<_> = it.take(10).collect();
+    assert_eq!(r, vec![0, 100, 200, 1, 2, 201, 3, 4, 202, 5]);
+
+    // Reset forgets that `c` is owed a turn, so `a` goes first again.
+    let r: Vec<_> = round_robin_weighted(vec![(0..100, 1), (100..101, 3), (200..300, 1)])
+        .on_exhausted(WeightPolicy::Reset)
+        .take(6).collect();
+    assert_eq!(r, vec![100, 0, 1, 200, 2, 201]);
+    let r: Vec<_> = round_robin_weighted(vec![(0..100, 1), (100..101, 3), (200..300, 1)])
+        .take(6).collect();
+    assert_eq!(r, vec![100, 0, 200, 201, 202, 1]);
+
+    let mut it = round_robin_weighted(vec![(0..100, 1), (100..101, 3)])
+        .on_exhausted(WeightPolicy::Stop);
+    assert_eq!(it.size_hint(), (1, Some(101)));
+    assert_eq!(it.by_ref().collect::<Vec<_>>(), vec![100, 0]);
+    assert_eq!(it.size_hint(), (0, Some(0)));
+    let rest = it.unwrap();
+    assert_eq!(rest.len(), 2);
+    assert_eq!(rest[0], (1..100, 1));
+}
+
+#[test]
+#[should_panic(expected = "weights must be non-zero")]
+fn test_round_robin_weighted_zero() {
+    let _ = round_robin_weighted(vec![(0..1, 0)]);
+}
+
+#[test]
+fn test_round_robin_pool() {
+    let mut pool = RoundRobinPool::new();
+    assert_eq!(pool.next(), None);
+
+    let a = pool.push(0..3);
+    let b = pool.push(10..12);
+    assert_eq!(pool.len(), 2);
+    assert_eq!(pool.size_hint(), (7, Some(7)));
+
+    let mut log = vec![];
+    while let Some(ev) = pool.next() {
+        match ev {
+            PoolEvent::Item(h, 1) if h == a => {
+                // Waits for `b` and `a` to have another turn, but `a` is removed first.
+                pool.push(20..21);
+            },
+            PoolEvent::Item(h, 11) if h == b => {
+                assert!(pool.remove(a).is_some());
+                assert!(!pool.contains(a));
+                assert_eq!(pool.remove(a), None);
+            },
+            _ => ()
+        }
+        log.push(ev);
+    }
+    let c = PoolHandle(2);
+    assert_eq!(log, vec![
+        PoolEvent::Item(a, 0), PoolEvent::Item(b, 10),
+        PoolEvent::Item(a, 1), PoolEvent::Item(b, 11),
+        PoolEvent::Item(c, 20), PoolEvent::Exhausted(b),
+        PoolEvent::Exhausted(c),
+    ]);
+    assert!(pool.is_empty());
+
+    // An empty pool can be restarted.
+    let d = pool.push(30..31);
+    assert!(d != a && d != b && d != c);
+    let r: Vec<_> = pool.by_ref().filter_map(PoolEvent::into_item).collect();
+    assert_eq!(r, vec![(d, 30)]);
+    assert!(pool.unwrap().is_empty());
+}
+
+#[cfg(test)]
+fn check_double_ended<It, E>(it: It) where It: DoubleEndedIterator<Item=E> + ExactSizeIterator + Clone, E: Clone + PartialEq + ::std::fmt::Debug {
+    let forward: Vec<_> = it.clone().collect();
+    assert_eq!(it.len(), forward.len());
+    let mut backward: Vec<_> = it.clone().rev().collect();
+    backward.reverse();
+    assert_eq!(backward, forward);
+
+    // Every split between taking from the front and from the back.
+    for front in 0..forward.len() + 1 {
+        let mut it = it.clone();
+        let mut r: Vec<_> = it.by_ref().take(front).collect();
+        assert_eq!(it.len(), forward.len() - r.len());
+        let mut back: Vec<_> = it.by_ref().rev().collect();
+        back.reverse();
+        r.extend(back);
+        assert_eq!(r, forward);
+    }
+}
+
+#[test]
+fn test_round_robin_double_ended() {
+    for n0 in 0..5 {
+        for n1 in 0..5 {
+            let v0: Vec<_> = (0..n0).collect();
+            let v1: Vec<_> = (100..100 + n1).collect();
+            let it = v0.clone().into_iter().round_robin(v1.clone().into_iter());
+            let expected = 2 * min(n0, n1) + if n0 > n1 { 1 } else { 0 };
+            assert_eq!(it.len(), expected);
+            check_double_ended(it);
+
+            let mut it = v0.clone().into_iter().round_robin(v1.clone().into_iter());
+            if it.next().is_some() {
+                assert_eq!(it.size_hint(), (expected - 1, Some(expected - 1)));
+                check_double_ended(it);
+            }
+
+            let it = v0.clone().into_iter().round_robin_longest(v1.clone().into_iter());
+            assert_eq!(it.len(), n0 + n1);
+            check_double_ended(it);
+
+            let mut it = v0.into_iter().round_robin_longest(v1.into_iter());
+            for _ in 0..3 {
+                it.next();
+                check_double_ended(it.clone());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_round_robin_unbounded() {
+    assert_eq!((0..).round_robin_longest(0..).size_hint(), (usize::MAX, None));
+    assert_eq!((0..).round_robin_longest(0..3).size_hint(), (usize::MAX, None));
+    assert_eq!((0..usize::MAX).round_robin_longest(0..usize::MAX).size_hint(), (usize::MAX, None));
+    assert_eq!((0..).round_robin(0..).size_hint(), (usize::MAX, None));
+    let r: Vec<_> = (0..).round_robin_longest(10..12).take(6).collect();
+    assert_eq!(r, vec![0, 10, 1, 11, 2, 3]);
+}
+
+#[test]
+fn test_round_robin_traits() {
+    let it = vec![0, 2].into_iter().round_robin(vec![1, 3, 5].into_iter());
+    assert_eq!(it.size_hint(), (4, Some(4)));
+    assert!(format!("{:?}", it).starts_with("RoundRobin {"));
+    let it = vec![0, 2].into_iter().round_robin_longest(vec![1, 3, 5].into_iter());
+    assert_eq!(it.clone().rev().collect::<Vec<_>>(), vec![5, 3, 2, 1, 0]);
+    assert!(format!("{:?}", it).starts_with("RoundRobinLongest {"));
+}
@@ -0,0 +1,316 @@
+/*
+Copyright ⓒ 2015 grabbag contributors.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+use std::cmp::{max, min, Ordering};
+use super::merge::{Compare, NaturalOrder};
+
+/**
+Set operations on sorted sequences.
+
+These operations treat each input as a sorted *multiset*: an element may appear several times, and the number of times it appears is significant.  If an element appears <em>m</em> times in the left input and <em>n</em> times in the right, each operation yields it a number of times as described below.  Each operation yields its result in sorted order, using constant memory.
+
+Where an element appears in both inputs, the copies from the left input are yielded in preference to those from the right.
+
+In debug builds, each operation will panic if it finds that either input is not sorted.
+*/
+pub trait SetOpIterator<E>: Iterator<Item=E> + Sized {
+    /**
+Creates an iterator which yields the union of two sorted sequences.
+
+Each element is yielded max(<em>m</em>, <em>n</em>) times.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::SetOpIterator;
+# fn main() {
+let a = vec![1, 2, 2, 4];
+let b = vec![2, 3, 4, 4];
+let r: Vec<_> = a.into_iter().union(b.into_iter()).collect();
+assert_eq!(r, vec![1, 2, 2, 3, 4, 4]);
+# }
+```
+    */
+    fn union<OtherIt>(self, other: OtherIt) -> SetOp<Self, OtherIt, E, NaturalOrder> where OtherIt: Iterator<Item=E>, E: Ord {
+        SetOp::new(Op::Union, self, other, NaturalOrder)
+    }
+
+    /**
+Creates an iterator which yields the union of two sequences, each sorted according to `compare`.
+    */
+    fn union_by<OtherIt, F>(self, other: OtherIt, compare: F) -> SetOp<Self, OtherIt, E, F> where OtherIt: Iterator<Item=E>, F: FnMut(&E, &E) -> Ordering {
+        SetOp::new(Op::Union, self, other, compare)
+    }
+
+    /**
+Creates an iterator which yields the intersection of two sorted sequences.
+
+Each element is yielded min(<em>m</em>, <em>n</em>) times.
+    */
+    fn intersection<OtherIt>(self, other: OtherIt) -> SetOp<Self, OtherIt, E, NaturalOrder> where OtherIt: Iterator<Item=E>, E: Ord {
+        SetOp::new(Op::Intersection, self, other, NaturalOrder)
+    }
+
+    /**
+Creates an iterator which yields the intersection of two sequences, each sorted according to `compare`.
+    */
+    fn intersection_by<OtherIt, F>(self, other: OtherIt, compare: F) -> SetOp<Self, OtherIt, E, F> where OtherIt: Iterator<Item=E>, F: FnMut(&E, &E) -> Ordering {
+        SetOp::new(Op::Intersection, self, other, compare)
+    }
+
+    /**
+Creates an iterator which yields the elements of the left sorted sequence which do not appear in the right sorted sequence.
+
+Each element is yielded max(<em>m</em> - <em>n</em>, 0) times.
+    */
+    fn difference<OtherIt>(self, other: OtherIt) -> SetOp<Self, OtherIt, E, NaturalOrder> where OtherIt: Iterator<Item=E>, E: Ord {
+        SetOp::new(Op::Difference, self, other, NaturalOrder)
+    }
+
+    /**
+Creates an iterator which yields the elements of the left sequence which do not appear in the right sequence, both sorted according to `compare`.
+    */
+    fn difference_by<OtherIt, F>(self, other: OtherIt, compare: F) -> SetOp<Self, OtherIt, E, F> where OtherIt: Iterator<Item=E>, F: FnMut(&E, &E) -> Ordering {
+        SetOp::new(Op::Difference, self, other, compare)
+    }
+
+    /**
+Creates an iterator which yields the elements which appear in one sorted sequence, but not the other.
+
+Each element is yielded |<em>m</em> - <em>n</em>| times.
+    */
+    fn symmetric_difference<OtherIt>(self, other: OtherIt) -> SetOp<Self, OtherIt, E, NaturalOrder> where OtherIt: Iterator<Item=E>, E: Ord {
+        SetOp::new(Op::SymmetricDifference, self, other, NaturalOrder)
+    }
+
+    /**
+Creates an iterator which yields the elements which appear in one sequence, but not the other, both sorted according to `compare`.
+    */
+    fn symmetric_difference_by<OtherIt, F>(self, other: OtherIt, compare: F) -> SetOp<Self, OtherIt, E, F> where OtherIt: Iterator<Item=E>, F: FnMut(&E, &E) -> Ordering {
+        SetOp::new(Op::SymmetricDifference, self, other, compare)
+    }
+}
+
+impl<It, E> SetOpIterator<E> for It where It: Iterator<Item=E> {}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Op {
+    Union,
+    Intersection,
+    Difference,
+    SymmetricDifference,
+}
+
+#[derive(Clone, Debug)]
+#[must_use = "iterator adaptors are lazy and do nothing unless consumed"]
+pub struct SetOp<LeftIt, RightIt, E, C> {
+    op: Op,
+    left: LeftIt,
+    right: RightIt,
+    left_head: Option<E>,
+    right_head: Option<E>,
+    started: bool,
+    compare: C,
+}
+
+impl<LeftIt, RightIt, E, C> SetOp<LeftIt, RightIt, E, C> {
+    fn new(op: Op, left: LeftIt, right: RightIt, compare: C) -> Self {
+        SetOp {
+            op,
+            left,
+            right,
+            left_head: None,
+            right_head: None,
+            started: false,
+            compare,
+        }
+    }
+
+    /**
+Unwraps the iterator, returning the underlying iterators, along with any element which has been pulled from each but not yet consumed.
+    */
+    pub fn unwrap(self) -> ((Option<E>, LeftIt), (Option<E>, RightIt)) {
+        let SetOp { left, right, left_head, right_head, .. } = self;
+        ((left_head, left), (right_head, right))
+    }
+}
+
+impl<LeftIt, RightIt, E, C> SetOp<LeftIt, RightIt, E, C> where LeftIt: Iterator<Item=E>, RightIt: Iterator<Item=E>, C: Compare<E> {
+    /*
+    Both of these pull the *next* element before giving up the current one, so that the order of the input can be checked without having to hold on to any extra elements.
+    */
+    fn take_left(&mut self) -> E {
+        let e = self.left_head.take().unwrap();
+        self.left_head = self.left.next();
+        if cfg!(debug_assertions) {
+            if let Some(ref next) = self.left_head {
+                if self.compare.compare(&e, next) == Ordering::Greater {
+                    panic!("set operation was given a left input which is not sorted");
+                }
+            }
+        }
+        e
+    }
+
+    fn take_right(&mut self) -> E {
+        let e = self.right_head.take().unwrap();
+        self.right_head = self.right.next();
+        if cfg!(debug_assertions) {
+            if let Some(ref next) = self.right_head {
+                if self.compare.compare(&e, next) == Ordering::Greater {
+                    panic!("set operation was given a right input which is not sorted");
+                }
+            }
+        }
+        e
+    }
+}
+
+impl<LeftIt, RightIt, E, C> Iterator for SetOp<LeftIt, RightIt, E, C> where LeftIt: Iterator<Item=E>, RightIt: Iterator<Item=E>, C: Compare<E> {
+    type Item = E;
+
+    fn next(&mut self) -> Option<E> {
+        if !self.started {
+            self.left_head = self.left.next();
+            self.right_head = self.right.next();
+            self.started = true;
+        }
+
+        loop {
+            let ord = match (&self.left_head, &self.right_head) {
+                (Some(l), Some(r)) => self.compare.compare(l, r),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => return None,
+            };
+
+            match (self.op, ord) {
+                (Op::Union, Ordering::Less)
+                | (Op::Difference, Ordering::Less)
+                | (Op::SymmetricDifference, Ordering::Less) => return Some(self.take_left()),
+
+                (Op::Union, Ordering::Greater)
+                | (Op::SymmetricDifference, Ordering::Greater) => return Some(self.take_right()),
+
+                (Op::Union, Ordering::Equal)
+                | (Op::Intersection, Ordering::Equal) => {
+                    self.take_right();
+                    return Some(self.take_left());
+                },
+
+                (Op::Intersection, Ordering::Less) => { self.take_left(); },
+
+                (Op::Intersection, Ordering::Greater)
+                | (Op::Difference, Ordering::Greater) => { self.take_right(); },
+
+                (Op::Difference, Ordering::Equal)
+                | (Op::SymmetricDifference, Ordering::Equal) => {
+                    self.take_left();
+                    self.take_right();
+                },
+            }
+
+            // Once one side runs out, there is nothing more to yield for some operations.
+            match (self.op, &self.left_head, &self.right_head) {
+                (Op::Intersection, None, _) | (Op::Intersection, _, None) | (Op::Difference, None, _) => return None,
+                _ => ()
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let add = |(l, mu): (usize, Option<usize>), head: &Option<E>| {
+            let h = if head.is_some() { 1 } else { 0 };
+            (l.saturating_add(h), mu.and_then(|u| u.checked_add(h)))
+        };
+
+        let (l0, mu0) = add(self.left.size_hint(), &self.left_head);
+        let (l1, mu1) = add(self.right.size_hint(), &self.right_head);
+        let sum = match (mu0, mu1) {
+            (Some(u0), Some(u1)) => u0.checked_add(u1),
+            _ => None
+        };
+
+        match self.op {
+            Op::Union => (max(l0, l1), sum),
+            Op::Intersection => (0, match (mu0, mu1) {
+                (Some(u0), Some(u1)) => Some(min(u0, u1)),
+                (Some(u), None) | (None, Some(u)) => Some(u),
+                (None, None) => None,
+            }),
+            Op::Difference => (match mu1 {
+                Some(u1) => l0.saturating_sub(u1),
+                None => 0
+            }, mu0),
+            Op::SymmetricDifference => (0, sum),
+        }
+    }
+}
+
+#[test]
+fn test_set_ops() {
+    let a = vec![1, 2, 2, 2, 4, 6, 6];
+    let b = vec![0, 2, 2, 3, 6, 6, 6, 7];
+
+    let r: Vec<_> = a.clone().into_iter().union(b.clone().into_iter()).collect();
+    assert_eq!(r, vec![0, 1, 2, 2, 2, 3, 4, 6, 6, 6, 7]);
+
+    let r: Vec<_> = a.clone().into_iter().intersection(b.clone().into_iter()).collect();
+    assert_eq!(r, vec![2, 2, 6, 6]);
+
+    let r: Vec<_> = a.clone().into_iter().difference(b.clone().into_iter()).collect();
+    assert_eq!(r, vec![1, 2, 4]);
+
+    let r: Vec<_> = b.clone().into_iter().difference(a.clone().into_iter()).collect();
+    assert_eq!(r, vec![0, 3, 6, 7]);
+
+    let r: Vec<_> = a.clone().into_iter().symmetric_difference(b.clone().into_iter()).collect();
+    assert_eq!(r, vec![0, 1, 2, 3, 4, 6, 7]);
+
+    let e: Vec<i32> = vec![];
+    assert_eq!(a.clone().into_iter().union(e.clone().into_iter()).collect::<Vec<_>>(), a);
+    assert_eq!(a.clone().into_iter().intersection(e.clone().into_iter()).count(), 0);
+    assert_eq!(e.clone().into_iter().difference(a.clone().into_iter()).count(), 0);
+    assert_eq!(e.into_iter().symmetric_difference(a.clone().into_iter()).collect::<Vec<_>>(), a);
+}
+
+#[test]
+fn test_set_ops_by() {
+    // Copies from the left are preferred.
+    let a = vec![(1, 'a'), (3, 'a')];
+    let b = vec![(1, 'b'), (2, 'b'), (3, 'b')];
+    let r: Vec<_> = a.clone().into_iter().union_by(b.clone().into_iter(), |x, y| x.0.cmp(&y.0)).collect();
+    assert_eq!(r, vec![(1, 'a'), (2, 'b'), (3, 'a')]);
+    let r: Vec<_> = a.clone().into_iter().intersection_by(b.clone().into_iter(), |x, y| x.0.cmp(&y.0)).collect();
+    assert_eq!(r, vec![(1, 'a'), (3, 'a')]);
+    let r: Vec<_> = b.clone().into_iter().difference_by(a.clone().into_iter(), |x, y| x.0.cmp(&y.0)).collect();
+    assert_eq!(r, vec![(2, 'b')]);
+    let r: Vec<_> = a.into_iter().symmetric_difference_by(b.into_iter(), |x, y| x.0.cmp(&y.0)).collect();
+    assert_eq!(r, vec![(2, 'b')]);
+
+    let a = vec![5, 3, 1];
+    let b = vec![4, 3, 2];
+    let r: Vec<_> = a.into_iter().union_by(b.into_iter(), |x, y| y.cmp(x)).collect();
+    assert_eq!(r, vec![5, 4, 3, 2, 1]);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "set operation was given a left input which is not sorted")]
+fn test_set_ops_unsorted_left() {
+    vec![1, 3, 2].into_iter().union(vec![0, 4].into_iter()).count();
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "set operation was given a right input which is not sorted")]
+fn test_set_ops_unsorted_right() {
+    vec![1, 2].into_iter().intersection(vec![2, 1].into_iter()).count();
+}
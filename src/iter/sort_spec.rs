@@ -0,0 +1,241 @@
+/*
+Copyright ⓒ 2015 grabbag contributors.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+use std::cmp::Ordering;
+use std::fmt;
+use std::rc::Rc;
+
+/**
+A multi-key sort order, built up one key at a time.
+
+Elements are compared by the first key; elements with equal first keys are compared by the second key, and so on.  Each key can be sorted in ascending or descending order.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::SortedIterator;
+# use grabbag::iter::sort_spec::SortSpec;
+# fn main() {
+let rows = vec![("west", 10, "b"), ("east", 5, "a"), ("west", 30, "a"), ("east", 5, "c")];
+let spec = SortSpec::new()
+    .asc(|r: &(&str, u32, &str)| r.0)
+    .desc(|r: &(&str, u32, &str)| r.1)
+    .asc(|r: &(&str, u32, &str)| r.2);
+let r: Vec<_> = rows.into_iter().sorted_by(|a, b| spec.compare(a, b)).collect();
+assert_eq!(r, vec![("east", 5, "a"), ("east", 5, "c"), ("west", 30, "a"), ("west", 10, "b")]);
+# }
+```
+*/
+pub struct SortSpec<'a, E> {
+    keys: Vec<SortKey<'a, E>>,
+}
+
+type CompareFn<'a, E> = Box<dyn Fn(&E, &E) -> Ordering + 'a>;
+type CacheFn<'a, E> = Box<dyn Fn(&[E]) -> Box<dyn KeyColumn + 'a> + 'a>;
+
+struct SortKey<'a, E> {
+    compare: CompareFn<'a, E>,
+    cache: CacheFn<'a, E>,
+}
+
+/*
+The keys of a single sort key for every element, computed ahead of time.
+*/
+trait KeyColumn {
+    fn compare(&self, i: usize, j: usize) -> Ordering;
+}
+
+struct Column<K> {
+    keys: Vec<K>,
+    descending: bool,
+}
+
+impl<K> KeyColumn for Column<K> where K: Ord {
+    fn compare(&self, i: usize, j: usize) -> Ordering {
+        match self.descending {
+            false => self.keys[i].cmp(&self.keys[j]),
+            true => self.keys[j].cmp(&self.keys[i]),
+        }
+    }
+}
+
+impl<'a, E> SortSpec<'a, E> {
+    /**
+Creates an empty sort specification, under which all elements compare equal.
+    */
+    pub fn new() -> SortSpec<'a, E> {
+        SortSpec {
+            keys: vec![],
+        }
+    }
+
+    /**
+Adds a key, sorted in ascending order.
+    */
+    pub fn asc<K, F>(self, key: F) -> SortSpec<'a, E> where K: Ord + 'a, F: Fn(&E) -> K + 'a {
+        self.push(key, false)
+    }
+
+    /**
+Adds a key, sorted in descending order.
+    */
+    pub fn desc<K, F>(self, key: F) -> SortSpec<'a, E> where K: Ord + 'a, F: Fn(&E) -> K + 'a {
+        self.push(key, true)
+    }
+
+    fn push<K, F>(mut self, key: F, descending: bool) -> SortSpec<'a, E> where K: Ord + 'a, F: Fn(&E) -> K + 'a {
+        let key = Rc::new(key);
+        let cache_key = key.clone();
+        self.keys.push(SortKey {
+            compare: Box::new(move |a, b| match descending {
+                false => key(a).cmp(&key(b)),
+                true => key(b).cmp(&key(a)),
+            }),
+            cache: Box::new(move |es| Box::new(Column {
+                keys: es.iter().map(|e| cache_key(e)).collect(),
+                descending,
+            })),
+        });
+        self
+    }
+
+    /**
+Returns the number of keys in this specification.
+    */
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /**
+Returns `true` if this specification has no keys.
+    */
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /**
+Compares two elements according to this specification.
+
+Keys are recomputed for every comparison.  If they are expensive to compute, use `sort_cached` or `SortedIterator::sorted_by_spec_cached` instead.
+    */
+    pub fn compare(&self, a: &E, b: &E) -> Ordering {
+        for key in &self.keys {
+            match (key.compare)(a, b) {
+                Ordering::Equal => (),
+                ord => return ord
+            }
+        }
+        Ordering::Equal
+    }
+
+    /**
+Sorts a slice according to this specification.  This sort is stable.
+    */
+    pub fn sort(&self, v: &mut [E]) {
+        v.sort_by(|a, b| self.compare(a, b));
+    }
+
+    /**
+Sorts a slice according to this specification, computing each key exactly once per element.  This sort is stable.
+
+This requires <em>O</em>(<em>n</em>) additional memory for each key, plus an index for each element.
+    */
+    pub fn sort_cached(&self, v: &mut [E]) {
+        let columns = self.keys.iter().map(|key| (key.cache)(v)).collect::<Vec<_>>();
+
+        let mut perm = (0..v.len()).collect::<Vec<_>>();
+        perm.sort_by(|&i, &j| {
+            for column in &columns {
+                match column.compare(i, j) {
+                    Ordering::Equal => (),
+                    ord => return ord
+                }
+            }
+            Ordering::Equal
+        });
+
+        // Move each element into place, following each cycle of the permutation.  `perm[i] == i` marks positions which are already done.
+        for start in 0..perm.len() {
+            let mut cur = start;
+            while perm[cur] != cur {
+                let next = perm[cur];
+                perm[cur] = cur;
+                if next == start {
+                    break;
+                }
+                v.swap(cur, next);
+                cur = next;
+            }
+        }
+    }
+}
+
+impl<'a, E> Default for SortSpec<'a, E> {
+    fn default() -> SortSpec<'a, E> {
+        SortSpec::new()
+    }
+}
+
+impl<'a, E> fmt::Debug for SortSpec<'a, E> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "SortSpec {{ keys: {} }}", self.keys.len())
+    }
+}
+
+#[test]
+fn test_sort_spec() {
+    #[derive(Clone, Debug, PartialEq)]
+    struct Row { region: &'static str, revenue: u32, name: &'static str }
+
+    let rows = vec![
+        Row { region: "west", revenue: 10, name: "b" },
+        Row { region: "east", revenue: 5, name: "c" },
+        Row { region: "west", revenue: 30, name: "a" },
+        Row { region: "east", revenue: 5, name: "a" },
+        Row { region: "east", revenue: 7, name: "z" },
+    ];
+    let spec = SortSpec::new()
+        .asc(|r: &Row| r.region)
+        .desc(|r: &Row| r.revenue)
+        .asc(|r: &Row| r.name);
+    assert_eq!(spec.len(), 3);
+
+    let expected = vec![
+        ("east", 7, "z"), ("east", 5, "a"), ("east", 5, "c"), ("west", 30, "a"), ("west", 10, "b"),
+    ];
+
+    let mut v = rows.clone();
+    spec.sort(&mut v);
+    let r: Vec<_> = v.iter().map(|r| (r.region, r.revenue, r.name)).collect();
+    assert_eq!(r, expected);
+
+    let mut v = rows;
+    spec.sort_cached(&mut v);
+    let r: Vec<_> = v.iter().map(|r| (r.region, r.revenue, r.name)).collect();
+    assert_eq!(r, expected);
+
+    assert_eq!(SortSpec::new().compare(&1, &2), Ordering::Equal);
+}
+
+#[test]
+fn test_sort_cached_counts() {
+    use std::cell::Cell;
+
+    // A reversed sequence, so that the permutation has several cycles.
+    let calls = Cell::new(0);
+    let spec = SortSpec::new().asc(|e: &u32| { calls.set(calls.get() + 1); e.to_string() });
+    let mut v: Vec<u32> = (0..50).rev().collect();
+    spec.sort_cached(&mut v);
+    assert_eq!(calls.get(), 50);
+
+    let mut expected: Vec<u32> = (0..50).collect();
+    expected.sort_by_key(|e| e.to_string());
+    assert_eq!(v, expected);
+}
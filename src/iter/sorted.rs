@@ -8,46 +8,807 @@ files in the project carrying such notice may not be copied, modified,
 or distributed except according to those terms.
 */
 use std::cmp::Ordering;
+use std::vec;
+use super::merge::{Compare, NaturalOrder};
+use super::natural::natural_cmp;
+use super::radix::{self, RadixKey};
+use super::sort_spec::SortSpec;
 
 /**
 Shorthand for collecting and sorting an iterator.
+
+Unless otherwise noted, sorting is stable: elements which compare equal remain in the order they appeared in the input iterator.
 */
-pub trait SortedIterator<E>: Iterator<Item=E> + Sized where E: Ord {
+pub trait SortedIterator<E>: Iterator<Item=E> + Sized {
+    /**
+Returns an iterator over the elements of the input iterator in sorted order.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::SortedIterator;
+# fn main() {
+let v = vec![3, 1, 2];
+let r: Vec<_> = v.into_iter().sorted().map(|e| e * 10).collect();
+assert_eq!(r, vec![10, 20, 30]);
+# }
+```
+    */
+    fn sorted(self) -> Sorted<E> where E: Ord;
+
+    /**
+Returns an iterator over the elements of the input iterator in sorted order, as specified by a comparison function.
+    */
+    fn sorted_by<F: FnMut(&E, &E) -> Ordering>(self, compare: F) -> Sorted<E>;
+
+    /**
+Returns an iterator over the elements of the input iterator, sorted by the key computed by `key`.
+
+`key` may be called more than once per element; if it is expensive, consider `sorted_by_cached_key` instead.
+    */
+    fn sorted_by_key<K: Ord, F: FnMut(&E) -> K>(self, key: F) -> Sorted<E>;
+
+    /**
+Returns an iterator over the elements of the input iterator, sorted by the key computed by `key`.
+
+`key` is called exactly once per element, and the results are kept until sorting is complete.
+    */
+    fn sorted_by_cached_key<K: Ord, F: FnMut(&E) -> K>(self, key: F) -> Sorted<E>;
+
+    /**
+Returns the permutation which would sort the input iterator: the indices of its elements, in the order that `sorted` would yield them.
+
+This is stable: the indices of elements which compare equal are in ascending order.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::SortedIterator;
+# fn main() {
+let names = vec!["carol", "alice", "bob"];
+let ages = vec![35, 30, 25];
+let order = names.iter().argsort();
+assert_eq!(order, vec![1, 2, 0]);
+let ages: Vec<_> = order.iter().map(|&i| ages[i]).collect();
+assert_eq!(ages, vec![30, 25, 35]);
+# }
+```
+    */
+    fn argsort(self) -> Vec<usize> where E: Ord;
+
+    /**
+Returns the permutation which would sort the input iterator, as specified by a comparison function.
+    */
+    fn argsort_by<F: FnMut(&E, &E) -> Ordering>(self, compare: F) -> Vec<usize>;
+
+    /**
+Returns the permutation which would sort the input iterator by the key computed by `key`.
+    */
+    fn argsort_by_key<K: Ord, F: FnMut(&E) -> K>(self, key: F) -> Vec<usize>;
+
+    /**
+Returns the rank of each element of the input iterator, in input order.
+
+Ranks start at 1 for the smallest element.  `ties` determines how elements which compare equal are ranked.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::SortedIterator;
+# use grabbag::iter::sorted::RankTies;
+# fn main() {
+let v = vec![10, 30, 20, 20];
+assert_eq!(v.iter().rank(RankTies::Average), vec![1.0, 4.0, 2.5, 2.5]);
+assert_eq!(v.iter().rank(RankTies::Min), vec![1.0, 4.0, 2.0, 2.0]);
+assert_eq!(v.iter().rank(RankTies::Dense), vec![1.0, 3.0, 2.0, 2.0]);
+assert_eq!(v.iter().rank(RankTies::Ordinal), vec![1.0, 4.0, 2.0, 3.0]);
+# }
+```
+    */
+    fn rank(self, ties: RankTies) -> Vec<f64> where E: Ord;
+
+    /**
+Returns the rank of each element of the input iterator, in input order, as specified by a comparison function.
+    */
+    fn rank_by<F: FnMut(&E, &E) -> Ordering>(self, ties: RankTies, compare: F) -> Vec<f64>;
+
+    /**
+Returns an iterator over the elements of the input iterator in sorted order.
+
+This sort is unstable: elements which compare equal may be reordered.  In exchange, it is typically faster, and the sort itself runs in place rather than needing the scratch buffer used by the stable sort.  The input is still collected into a `Vec` first.
+    */
+    fn sorted_unstable(self) -> Sorted<E> where E: Ord;
+
     /**
-Returns a `Vec` with the elements of the input iterator in sorted order.
+Returns an iterator over the elements of the input iterator in sorted order, as specified by a comparison function.
+
+This sort is unstable: elements which compare equal may be reordered.
     */
-    fn sorted(self) -> Vec<E>;
+    fn sorted_unstable_by<F: FnMut(&E, &E) -> Ordering>(self, compare: F) -> Sorted<E>;
 
     /**
-Returns a `Vec` with the elements of the input iterator in sorted order, as specified by a comparison function.
+Returns an iterator over the elements of the input iterator, sorted by the key computed by `key`.
+
+This sort is unstable: elements which compare equal may be reordered.
     */
-    fn sorted_by<F: FnMut(&E, &E) -> Ordering>(self, compare: F) -> Vec<E>;
+    fn sorted_unstable_by_key<K: Ord, F: FnMut(&E) -> K>(self, key: F) -> Sorted<E>;
+
+    /**
+Returns an iterator over the elements of the input iterator in descending order.
+
+This sort is stable: elements which compare equal remain in the order they appeared in the input iterator.
+    */
+    fn sorted_desc(self) -> Sorted<E> where E: Ord;
+
+    /**
+Returns an iterator over the elements of the input iterator, sorted in descending order by the key computed by `key`.
+
+This sort is stable: elements with equal keys remain in the order they appeared in the input iterator.
+    */
+    fn sorted_by_key_desc<K: Ord, F: FnMut(&E) -> K>(self, key: F) -> Sorted<E>;
+
+    /**
+Returns an iterator over the elements of the input iterator in natural order.
+
+Natural order compares runs of digits by their numeric value, so that `"file2"` comes before `"file10"`.  See the `natural` module for details, and `natural::NaturalCmp` for further options.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::SortedIterator;
+# fn main() {
+let v = vec!["file10", "file2", "file1"];
+let r: Vec<_> = v.into_iter().sorted_natural().collect();
+assert_eq!(r, vec!["file1", "file2", "file10"]);
+# }
+```
+    */
+    fn sorted_natural(self) -> Sorted<E> where E: AsRef<str>;
+
+    /**
+Returns an iterator over the elements of the input iterator, sorted in natural order by the string returned by `key`.
+    */
+    fn sorted_natural_by_key<F: FnMut(&E) -> &str>(self, key: F) -> Sorted<E>;
+
+    /**
+Returns an iterator over the elements of the input iterator, sorted according to a multi-key `SortSpec`.
+
+Keys may be computed more than once per element; if they are expensive, consider `sorted_by_spec_cached` instead.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::SortedIterator;
+# use grabbag::iter::sort_spec::SortSpec;
+# fn main() {
+let spec = SortSpec::new().asc(|e: &(u8, u8)| e.0).desc(|e: &(u8, u8)| e.1);
+let v = vec![(1, 1), (0, 5), (1, 2), (0, 3)];
+let r: Vec<_> = v.into_iter().sorted_by_spec(&spec).collect();
+assert_eq!(r, vec![(0, 5), (0, 3), (1, 2), (1, 1)]);
+# }
+```
+    */
+    fn sorted_by_spec(self, spec: &SortSpec<E>) -> Sorted<E>;
+
+    /**
+Returns an iterator over the elements of the input iterator, sorted according to a multi-key `SortSpec`.
+
+Each key is computed exactly once per element, and the results are kept until sorting is complete.
+    */
+    fn sorted_by_spec_cached(self, spec: &SortSpec<E>) -> Sorted<E>;
+
+    /**
+Returns an iterator over the elements of the input iterator in sorted order, using a radix sort.
+
+This produces exactly the same order as `sorted`.  Floating point values are sorted using the IEEE 754 `totalOrder` predicate.  See the `radix` module for details, including when this is likely to be faster than a comparison sort.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::SortedIterator;
+# fn main() {
+let v = vec![300u32, -5i32 as u32, 7, 0];
+let r: Vec<_> = v.into_iter().sorted_radix().collect();
+assert_eq!(r, vec![0, 7, 300, -5i32 as u32]);
+
+let v = vec![300i32, -5, 7, 0];
+let r: Vec<_> = v.into_iter().sorted_radix().collect();
+assert_eq!(r, vec![-5, 0, 7, 300]);
+# }
+```
+    */
+    fn sorted_radix(self) -> Sorted<E> where E: RadixKey;
+
+    /**
+Returns an iterator over the elements of the input iterator, sorted by the fixed-width key computed by `key` using a radix sort.
+
+`key` is called exactly once per element.  This sort is stable, and produces exactly the same order as `sorted_by_key`.
+    */
+    fn sorted_radix_by_key<K: RadixKey, F: FnMut(&E) -> K>(self, key: F) -> Sorted<E>;
+
+    /**
+Returns an iterator over the elements of the input iterator, sorted by their bytes using a radix sort.
+
+This produces exactly the same order as `sorted` for byte strings and `str`s.
+    */
+    fn sorted_radix_bytes(self) -> Sorted<E> where E: AsRef<[u8]>;
+
+    /**
+Returns an iterator over the elements of the input iterator, sorted by the byte string returned by `key` using a radix sort.
+
+This sort is stable, and produces exactly the same order as `sorted_by_key`.
+    */
+    fn sorted_radix_bytes_by_key<F: FnMut(&E) -> &[u8]>(self, key: F) -> Sorted<E>;
+
+    /**
+Returns an iterator over the `k` smallest elements of the input iterator, in sorted order.
+
+This only keeps `k` elements in memory at a time, and takes <em>O</em>(<em>n</em> log <em>k</em>) time.  Where elements compare equal, those which appeared first in the input iterator are preferred.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::SortedIterator;
+# fn main() {
+let r: Vec<_> = (0..1000).rev().k_smallest(3).collect();
+assert_eq!(r, vec![0, 1, 2]);
+# }
+```
+    */
+    fn k_smallest(self, k: usize) -> Sorted<E> where E: Ord;
+
+    /**
+Returns an iterator over the `k` smallest elements of the input iterator, in sorted order, as specified by a comparison function.
+    */
+    fn k_smallest_by<F: FnMut(&E, &E) -> Ordering>(self, k: usize, compare: F) -> Sorted<E>;
+
+    /**
+Returns an iterator over the `k` elements of the input iterator with the smallest keys, in sorted order.
+    */
+    fn k_smallest_by_key<K: Ord, F: FnMut(&E) -> K>(self, k: usize, key: F) -> Sorted<E>;
+
+    /**
+Returns an iterator over the `k` largest elements of the input iterator, in descending order.
+
+This only keeps `k` elements in memory at a time, and takes <em>O</em>(<em>n</em> log <em>k</em>) time.  Where elements compare equal, those which appeared first in the input iterator are preferred.
+    */
+    fn k_largest(self, k: usize) -> Sorted<E> where E: Ord;
+
+    /**
+Returns an iterator over the `k` largest elements of the input iterator, in descending order, as specified by a comparison function.
+    */
+    fn k_largest_by<F: FnMut(&E, &E) -> Ordering>(self, k: usize, compare: F) -> Sorted<E>;
+
+    /**
+Returns an iterator over the `k` elements of the input iterator with the largest keys, in descending order.
+    */
+    fn k_largest_by_key<K: Ord, F: FnMut(&E) -> K>(self, k: usize, key: F) -> Sorted<E>;
+
+    /**
+Returns an iterator which lazily yields the elements of the input iterator in sorted order.
+
+The elements are collected and arranged into a heap in <em>O</em>(<em>n</em>) time; each element is then removed from the heap as it is yielded, in <em>O</em>(log <em>n</em>) time.  As such, taking only the first few elements is much cheaper than sorting the whole sequence.  This sort is stable.
+    */
+    fn sorted_lazy(self) -> SortedLazy<E, NaturalOrder> where E: Ord;
+
+    /**
+Returns an iterator which lazily yields the elements of the input iterator in sorted order, as specified by a comparison function.
+    */
+    fn sorted_lazy_by<F: FnMut(&E, &E) -> Ordering>(self, compare: F) -> SortedLazy<E, F>;
 }
 
-impl<It, E> SortedIterator<E> for It where It: Iterator<Item=E>, E: Ord {
-    fn sorted(self) -> Vec<E> {
+impl<It, E> SortedIterator<E> for It where It: Iterator<Item=E> {
+    fn sorted(self) -> Sorted<E> where E: Ord {
         let mut v = self.collect::<Vec<_>>();
         v.sort();
-        v
+        Sorted::new(v)
     }
 
-    fn sorted_by<F: FnMut(&E, &E) -> Ordering>(self, compare: F) -> Vec<E> {
+    fn sorted_by<F: FnMut(&E, &E) -> Ordering>(self, compare: F) -> Sorted<E> {
         let mut v = self.collect::<Vec<_>>();
         v.sort_by(compare);
-        v
+        Sorted::new(v)
+    }
+
+    fn sorted_by_key<K: Ord, F: FnMut(&E) -> K>(self, key: F) -> Sorted<E> {
+        let mut v = self.collect::<Vec<_>>();
+        v.sort_by_key(key);
+        Sorted::new(v)
+    }
+
+    fn sorted_by_cached_key<K: Ord, F: FnMut(&E) -> K>(self, key: F) -> Sorted<E> {
+        let mut v = self.collect::<Vec<_>>();
+        v.sort_by_cached_key(key);
+        Sorted::new(v)
+    }
+
+    fn argsort(self) -> Vec<usize> where E: Ord {
+        self.argsort_by(Ord::cmp)
+    }
+
+    fn argsort_by<F: FnMut(&E, &E) -> Ordering>(self, mut compare: F) -> Vec<usize> {
+        let v = self.collect::<Vec<_>>();
+        let mut idx = (0..v.len()).collect::<Vec<_>>();
+        idx.sort_by(|&i, &j| compare(&v[i], &v[j]));
+        idx
+    }
+
+    fn argsort_by_key<K: Ord, F: FnMut(&E) -> K>(self, mut key: F) -> Vec<usize> {
+        self.argsort_by(|a, b| key(a).cmp(&key(b)))
+    }
+
+    fn rank(self, ties: RankTies) -> Vec<f64> where E: Ord {
+        self.rank_by(ties, Ord::cmp)
+    }
+
+    fn rank_by<F: FnMut(&E, &E) -> Ordering>(self, ties: RankTies, mut compare: F) -> Vec<f64> {
+        let v = self.collect::<Vec<_>>();
+        let mut idx = (0..v.len()).collect::<Vec<_>>();
+        idx.sort_by(|&i, &j| compare(&v[i], &v[j]));
+
+        let mut ranks = vec![0.0; v.len()];
+        let mut start = 0;
+        let mut dense = 0;
+        while start < idx.len() {
+            let mut end = start + 1;
+            while end < idx.len() && compare(&v[idx[start]], &v[idx[end]]) == Ordering::Equal {
+                end += 1;
+            }
+            dense += 1;
+            for (offset, &i) in idx[start..end].iter().enumerate() {
+                ranks[i] = match ties {
+                    RankTies::Average => (start + end + 1) as f64 / 2.0,
+                    RankTies::Min => (start + 1) as f64,
+                    RankTies::Dense => dense as f64,
+                    RankTies::Ordinal => (start + offset + 1) as f64,
+                };
+            }
+            start = end;
+        }
+        ranks
+    }
+
+    fn sorted_unstable(self) -> Sorted<E> where E: Ord {
+        let mut v = self.collect::<Vec<_>>();
+        v.sort_unstable();
+        Sorted::new(v)
+    }
+
+    fn sorted_unstable_by<F: FnMut(&E, &E) -> Ordering>(self, compare: F) -> Sorted<E> {
+        let mut v = self.collect::<Vec<_>>();
+        v.sort_unstable_by(compare);
+        Sorted::new(v)
+    }
+
+    fn sorted_unstable_by_key<K: Ord, F: FnMut(&E) -> K>(self, key: F) -> Sorted<E> {
+        let mut v = self.collect::<Vec<_>>();
+        v.sort_unstable_by_key(key);
+        Sorted::new(v)
+    }
+
+    fn sorted_desc(self) -> Sorted<E> where E: Ord {
+        self.sorted_by(|a, b| b.cmp(a))
+    }
+
+    fn sorted_by_key_desc<K: Ord, F: FnMut(&E) -> K>(self, mut key: F) -> Sorted<E> {
+        self.sorted_by(|a, b| key(b).cmp(&key(a)))
+    }
+
+    fn sorted_natural(self) -> Sorted<E> where E: AsRef<str> {
+        self.sorted_by(|a, b| natural_cmp(a.as_ref(), b.as_ref()))
+    }
+
+    fn sorted_natural_by_key<F: FnMut(&E) -> &str>(self, mut key: F) -> Sorted<E> {
+        let mut v = self.collect::<Vec<_>>();
+        v.sort_by(|a, b| natural_cmp(key(a), key(b)));
+        Sorted::new(v)
+    }
+
+    fn sorted_by_spec(self, spec: &SortSpec<E>) -> Sorted<E> {
+        let mut v = self.collect::<Vec<_>>();
+        spec.sort(&mut v);
+        Sorted::new(v)
+    }
+
+    fn sorted_by_spec_cached(self, spec: &SortSpec<E>) -> Sorted<E> {
+        let mut v = self.collect::<Vec<_>>();
+        spec.sort_cached(&mut v);
+        Sorted::new(v)
+    }
+
+    fn sorted_radix(self) -> Sorted<E> where E: RadixKey {
+        Sorted::new(radix::sort(self.collect()))
+    }
+
+    fn sorted_radix_by_key<K: RadixKey, F: FnMut(&E) -> K>(self, key: F) -> Sorted<E> {
+        Sorted::new(radix::sort_by_key(self.collect(), key))
+    }
+
+    fn sorted_radix_bytes(self) -> Sorted<E> where E: AsRef<[u8]> {
+        Sorted::new(radix::sort_bytes_by_key(self.collect(), |e| e.as_ref()))
+    }
+
+    fn sorted_radix_bytes_by_key<F: FnMut(&E) -> &[u8]>(self, key: F) -> Sorted<E> {
+        Sorted::new(radix::sort_bytes_by_key(self.collect(), key))
+    }
+
+    fn k_smallest(self, k: usize) -> Sorted<E> where E: Ord {
+        Sorted::new(k_smallest_by(self, k, |a: &E, b: &E| a.cmp(b)))
+    }
+
+    fn k_smallest_by<F: FnMut(&E, &E) -> Ordering>(self, k: usize, compare: F) -> Sorted<E> {
+        Sorted::new(k_smallest_by(self, k, compare))
+    }
+
+    fn k_smallest_by_key<K: Ord, F: FnMut(&E) -> K>(self, k: usize, mut key: F) -> Sorted<E> {
+        Sorted::new(k_smallest_by(self, k, |a: &E, b: &E| key(a).cmp(&key(b))))
+    }
+
+    fn k_largest(self, k: usize) -> Sorted<E> where E: Ord {
+        Sorted::new(k_smallest_by(self, k, |a: &E, b: &E| b.cmp(a)))
+    }
+
+    fn k_largest_by<F: FnMut(&E, &E) -> Ordering>(self, k: usize, mut compare: F) -> Sorted<E> {
+        Sorted::new(k_smallest_by(self, k, |a: &E, b: &E| compare(b, a)))
+    }
+
+    fn k_largest_by_key<K: Ord, F: FnMut(&E) -> K>(self, k: usize, mut key: F) -> Sorted<E> {
+        Sorted::new(k_smallest_by(self, k, |a: &E, b: &E| key(b).cmp(&key(a))))
+    }
+
+    fn sorted_lazy(self) -> SortedLazy<E, NaturalOrder> where E: Ord {
+        SortedLazy::new(self, NaturalOrder)
+    }
+
+    fn sorted_lazy_by<F: FnMut(&E, &E) -> Ordering>(self, compare: F) -> SortedLazy<E, F> {
+        SortedLazy::new(self, compare)
+    }
+}
+
+/*
+Moves `heap[i]` down until neither of its children should come `before` it.
+*/
+fn sift_down<T, F>(heap: &mut [T], mut i: usize, before: &mut F) where F: FnMut(&T, &T) -> bool {
+    loop {
+        let (l, r) = (2*i + 1, 2*i + 2);
+        let mut first = i;
+        if l < heap.len() && before(&heap[l], &heap[first]) {
+            first = l;
+        }
+        if r < heap.len() && before(&heap[r], &heap[first]) {
+            first = r;
+        }
+        if first == i {
+            break;
+        }
+        heap.swap(i, first);
+        i = first;
+    }
+}
+
+/*
+Returns the `k` smallest elements of `iter` in sorted order.
+
+This keeps a max-heap of the `k` smallest elements seen so far.  Each element is tagged with its position in the input, so that ties are broken in favour of earlier elements.
+*/
+fn k_smallest_by<It, E, F>(iter: It, k: usize, mut compare: F) -> Vec<E> where It: Iterator<Item=E>, F: FnMut(&E, &E) -> Ordering {
+    if k == 0 {
+        return vec![];
+    }
+
+    let mut compare = |a: &(usize, E), b: &(usize, E)| compare(&a.1, &b.1).then(a.0.cmp(&b.0));
+    // `k` may be far larger than the input, so only reserve what the input promises.
+    let mut heap = Vec::with_capacity(k.min(iter.size_hint().0));
+    for entry in iter.enumerate() {
+        if heap.len() < k {
+            heap.push(entry);
+            if heap.len() == k {
+                for i in (0..k/2).rev() {
+                    sift_down(&mut heap, i, &mut |a, b| compare(a, b) == Ordering::Greater);
+                }
+            }
+        } else if compare(&entry, &heap[0]) == Ordering::Less {
+            heap[0] = entry;
+            sift_down(&mut heap, 0, &mut |a, b| compare(a, b) == Ordering::Greater);
+        }
+    }
+
+    heap.sort_by(compare);
+    heap.into_iter().map(|(_, e)| e).collect()
+}
+
+/**
+How `SortedIterator::rank` ranks elements which compare equal.
+*/
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum RankTies {
+    /// Each element gets the average of the ranks the group would otherwise occupy.
+    Average,
+    /// Each element gets the lowest rank the group would otherwise occupy.
+    Min,
+    /// Each element gets the rank of its group; ranks have no gaps.
+    Dense,
+    /// Each element gets a distinct rank, in the order the elements appeared.
+    Ordinal,
+}
+
+/**
+An iterator over the elements of a sequence in sorted order.
+*/
+#[derive(Clone, Debug)]
+pub struct Sorted<E> {
+    iter: vec::IntoIter<E>,
+}
+
+impl<E> Sorted<E> {
+    fn new(v: Vec<E>) -> Sorted<E> {
+        Sorted {
+            iter: v.into_iter(),
+        }
+    }
+
+    /**
+Returns the remaining elements as a slice.
+    */
+    pub fn as_slice(&self) -> &[E] {
+        self.iter.as_slice()
+    }
+
+    /**
+Unwraps the iterator, returning the remaining elements in a `Vec`.
+    */
+    pub fn unwrap(self) -> Vec<E> {
+        self.iter.collect()
+    }
+}
+
+impl<E> Iterator for Sorted<E> {
+    type Item = E;
+
+    fn next(&mut self) -> Option<E> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<E> DoubleEndedIterator for Sorted<E> {
+    fn next_back(&mut self) -> Option<E> {
+        self.iter.next_back()
     }
 }
 
+impl<E> ExactSizeIterator for Sorted<E> {}
+
+/**
+An iterator which lazily yields the elements of a sequence in sorted order.
+*/
+#[derive(Clone, Debug)]
+pub struct SortedLazy<E, C> {
+    heap: Vec<(usize, E)>,
+    compare: C,
+}
+
+impl<E, C> SortedLazy<E, C> where C: Compare<E> {
+    fn new<It>(iter: It, compare: C) -> SortedLazy<E, C> where It: Iterator<Item=E> {
+        let mut lazy = SortedLazy {
+            heap: iter.enumerate().collect(),
+            compare,
+        };
+        for i in (0..lazy.heap.len()/2).rev() {
+            lazy.sift_down(i);
+        }
+        lazy
+    }
+
+    fn sift_down(&mut self, i: usize) {
+        let compare = &mut self.compare;
+        sift_down(&mut self.heap, i, &mut |a, b| compare.compare(&a.1, &b.1).then(a.0.cmp(&b.0)) == Ordering::Less);
+    }
+}
+
+impl<E, C> SortedLazy<E, C> {
+    /**
+Unwraps the iterator, returning the elements which have not yet been yielded, in no particular order.
+    */
+    pub fn unwrap(self) -> Vec<E> {
+        self.heap.into_iter().map(|(_, e)| e).collect()
+    }
+}
+
+impl<E, C> Iterator for SortedLazy<E, C> where C: Compare<E> {
+    type Item = E;
+
+    fn next(&mut self) -> Option<E> {
+        if self.heap.is_empty() {
+            return None;
+        }
+        let (_, e) = self.heap.swap_remove(0);
+        self.sift_down(0);
+        Some(e)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.heap.len(), Some(self.heap.len()))
+    }
+}
+
+impl<E, C> ExactSizeIterator for SortedLazy<E, C> where C: Compare<E> {}
+
 #[test]
 fn test_sorted() {
     let v = vec![1usize, 3, 2, 0, 4];
     let s = v.into_iter().sorted();
-    assert_eq!(s, vec![0usize, 1, 2, 3, 4]);
+    assert_eq!(s.collect::<Vec<_>>(), vec![0usize, 1, 2, 3, 4]);
+
+    let v = vec![1usize, 3, 2, 0, 4];
+    let mut s = v.into_iter().sorted();
+    assert_eq!(s.len(), 5);
+    assert_eq!(s.next(), Some(0));
+    assert_eq!(s.next_back(), Some(4));
+    assert_eq!(s.as_slice(), &[1, 2, 3]);
+    assert_eq!(s.unwrap(), vec![1, 2, 3]);
 }
 
 #[test]
 fn test_sorted_by() {
     let v = vec![1usize, 3, 2, 0, 4];
     let s = v.into_iter().sorted_by(|a,b| (!*a).cmp(&!*b));
-    assert_eq!(s, vec![4, 3, 2, 1, 0usize]);
+    assert_eq!(s.collect::<Vec<_>>(), vec![4, 3, 2, 1, 0usize]);
+}
+
+#[test]
+fn test_sorted_by_key() {
+    let v = vec!["b", "A", "c", "a", "B"];
+    let s: Vec<_> = v.clone().into_iter().sorted_by_key(|e| e.to_lowercase()).collect();
+    assert_eq!(s, vec!["A", "a", "b", "B", "c"]);
+
+    let mut calls = 0;
+    let s: Vec<_> = v.into_iter().sorted_by_cached_key(|e| { calls += 1; e.to_lowercase() }).collect();
+    assert_eq!(s, vec!["A", "a", "b", "B", "c"]);
+    assert_eq!(calls, 5);
+}
+
+#[test]
+fn test_argsort() {
+    let v = [3, 1, 2, 1];
+    assert_eq!(v.iter().argsort(), vec![1, 3, 2, 0]);
+    assert_eq!(v.iter().argsort_by(|a, b| b.cmp(a)), vec![0, 2, 1, 3]);
+    assert_eq!(v.iter().argsort_by_key(|&&e| e % 2), vec![2, 0, 1, 3]);
+    assert_eq!(Vec::<i32>::new().into_iter().argsort(), vec![]);
+}
+
+#[test]
+fn test_rank() {
+    let v = ["b", "a", "c", "a", "b", "a"];
+    assert_eq!(v.iter().rank(RankTies::Average), vec![4.5, 2.0, 6.0, 2.0, 4.5, 2.0]);
+    assert_eq!(v.iter().rank(RankTies::Min), vec![4.0, 1.0, 6.0, 1.0, 4.0, 1.0]);
+    assert_eq!(v.iter().rank(RankTies::Dense), vec![2.0, 1.0, 3.0, 1.0, 2.0, 1.0]);
+    assert_eq!(v.iter().rank(RankTies::Ordinal), vec![4.0, 1.0, 6.0, 2.0, 5.0, 3.0]);
+    assert_eq!(v.iter().rank_by(RankTies::Min, |a, b| b.cmp(a)), vec![2.0, 4.0, 1.0, 4.0, 2.0, 4.0]);
+    assert_eq!(Vec::<i32>::new().into_iter().rank(RankTies::Average), Vec::<f64>::new());
+}
+
+#[test]
+fn test_sorted_unstable() {
+    let v = vec![1usize, 3, 2, 0, 4];
+    let s: Vec<_> = v.clone().into_iter().sorted_unstable().collect();
+    assert_eq!(s, vec![0, 1, 2, 3, 4]);
+
+    let s: Vec<_> = v.clone().into_iter().sorted_unstable_by(|a, b| b.cmp(a)).collect();
+    assert_eq!(s, vec![4, 3, 2, 1, 0]);
+
+    let s: Vec<_> = v.into_iter().sorted_unstable_by_key(|&e| e % 3).map(|e| e % 3).collect();
+    assert_eq!(s, vec![0, 0, 1, 1, 2]);
+}
+
+#[test]
+fn test_sorted_desc() {
+    let v = vec![1usize, 3, 2, 0, 4];
+    let s: Vec<_> = v.into_iter().sorted_desc().collect();
+    assert_eq!(s, vec![4, 3, 2, 1, 0]);
+
+    let v = vec![(1, 'a'), (2, 'a'), (1, 'b'), (2, 'b')];
+    let s: Vec<_> = v.into_iter().sorted_by_key_desc(|e| e.0).collect();
+    assert_eq!(s, vec![(2, 'a'), (2, 'b'), (1, 'a'), (1, 'b')]);
+}
+
+#[test]
+fn test_sorted_natural() {
+    let v = vec!["v1.10", "v1.9", "v1.09", "v10.0", "v2"];
+    let r: Vec<_> = v.into_iter().sorted_natural().collect();
+    assert_eq!(r, vec!["v1.9", "v1.09", "v1.10", "v2", "v10.0"]);
+
+    let v = vec![(3, "img12.png".to_owned()), (1, "img10.png".to_owned()), (2, "img2.png".to_owned())];
+    let r: Vec<_> = v.into_iter().sorted_natural_by_key(|e| &e.1).map(|e| e.0).collect();
+    assert_eq!(r, vec![2, 1, 3]);
+}
+
+#[test]
+fn test_sorted_by_spec() {
+    let spec = SortSpec::new().desc(|e: &(i32, &str)| e.0 % 3).asc(|e: &(i32, &str)| e.1.len());
+    let v = vec![(3, "ccc"), (4, "a"), (5, "bb"), (7, "dddd"), (6, "e"), (1, "ff")];
+    let expected = vec![(5, "bb"), (4, "a"), (1, "ff"), (7, "dddd"), (6, "e"), (3, "ccc")];
+    let r: Vec<_> = v.clone().into_iter().sorted_by_spec(&spec).collect();
+    assert_eq!(r, expected);
+    let r: Vec<_> = v.into_iter().sorted_by_spec_cached(&spec).collect();
+    assert_eq!(r, expected);
+}
+
+#[test]
+fn test_sorted_radix() {
+    let v: Vec<u64> = (0..20000u64).map(|e| e.wrapping_mul(0x9e37_79b9_7f4a_7c15)).collect();
+    let expected: Vec<_> = v.clone().into_iter().sorted().collect();
+    let r: Vec<_> = v.into_iter().sorted_radix().collect();
+    assert_eq!(r, expected);
+
+    let v: Vec<(i16, u32)> = (0..5000u32).map(|e| ((e.wrapping_mul(2_654_435_761) >> 20) as i16, e)).collect();
+    let expected: Vec<_> = v.clone().into_iter().sorted_by_key(|e| e.0).collect();
+    let r: Vec<_> = v.into_iter().sorted_radix_by_key(|e| e.0).collect();
+    assert_eq!(r, expected);
+
+    let v = vec!["pear", "apple", "", "peach", "app", "apple"];
+    let r: Vec<_> = v.clone().into_iter().sorted_radix_bytes().collect();
+    assert_eq!(r, v.clone().into_iter().sorted().collect::<Vec<_>>());
+
+    let v: Vec<_> = v.into_iter().enumerate().collect();
+    let r: Vec<_> = v.clone().into_iter().sorted_radix_bytes_by_key(|e| e.1.as_bytes()).collect();
+    assert_eq!(r, v.into_iter().sorted_by_key(|e| e.1).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_k_smallest() {
+    let v = vec![5usize, 1, 9, 3, 7, 3, 0, 8];
+    let r: Vec<_> = v.clone().into_iter().k_smallest(3).collect();
+    assert_eq!(r, vec![0, 1, 3]);
+    let r: Vec<_> = v.clone().into_iter().k_largest(3).collect();
+    assert_eq!(r, vec![9, 8, 7]);
+    let r: Vec<_> = v.clone().into_iter().k_smallest(0).collect();
+    assert_eq!(r, Vec::<usize>::new());
+    let r: Vec<_> = v.clone().into_iter().k_smallest(100).collect();
+    assert_eq!(r, vec![0, 1, 3, 3, 5, 7, 8, 9]);
+    let r: Vec<_> = v.clone().into_iter().k_smallest(usize::MAX).collect();
+    assert_eq!(r, vec![0, 1, 3, 3, 5, 7, 8, 9]);
+    let r: Vec<_> = v.clone().into_iter().k_largest(usize::MAX).collect();
+    assert_eq!(r, vec![9, 8, 7, 5, 3, 3, 1, 0]);
+    let r: Vec<_> = v.clone().into_iter().filter(|_| true).k_smallest(usize::MAX).collect();
+    assert_eq!(r, vec![0, 1, 3, 3, 5, 7, 8, 9]);
+    let r: Vec<_> = v.into_iter().k_smallest_by(2, |a, b| b.cmp(a)).collect();
+    assert_eq!(r, vec![9, 8]);
+
+    // Ties favour earlier elements.
+    let v = vec![(1, 'a'), (0, 'b'), (1, 'c'), (1, 'd'), (2, 'e')];
+    let r: Vec<_> = v.clone().into_iter().k_smallest_by_key(3, |e| e.0).collect();
+    assert_eq!(r, vec![(0, 'b'), (1, 'a'), (1, 'c')]);
+    let r: Vec<_> = v.clone().into_iter().k_largest_by_key(3, |e| e.0).collect();
+    assert_eq!(r, vec![(2, 'e'), (1, 'a'), (1, 'c')]);
+    let r: Vec<_> = v.into_iter().k_largest_by(2, |a, b| a.0.cmp(&b.0)).collect();
+    assert_eq!(r, vec![(2, 'e'), (1, 'a')]);
+}
+
+#[test]
+fn test_sorted_lazy() {
+    let v = vec![5usize, 1, 9, 3, 7, 3, 0, 8];
+    let mut it = v.clone().into_iter().sorted_lazy();
+    assert_eq!(it.len(), 8);
+    assert_eq!(it.next(), Some(0));
+    assert_eq!(it.next(), Some(1));
+    assert_eq!(it.len(), 6);
+    assert_eq!(it.collect::<Vec<_>>(), vec![3, 3, 5, 7, 8, 9]);
+
+    let r: Vec<_> = v.into_iter().sorted_lazy_by(|a, b| b.cmp(a)).take(3).collect();
+    assert_eq!(r, vec![9, 8, 7]);
+
+    let v = vec![(1, 'a'), (0, 'b'), (1, 'c'), (1, 'd'), (0, 'e')];
+    let r: Vec<_> = v.into_iter().sorted_lazy_by(|a, b| a.0.cmp(&b.0)).collect();
+    assert_eq!(r, vec![(0, 'b'), (0, 'e'), (1, 'a'), (1, 'c'), (1, 'd')]);
 }
@@ -7,6 +7,10 @@ Licensed under the MIT license (see LICENSE or <http://opensource.org
 files in the project carrying such notice may not be copied, modified,
 or distributed except according to those terms.
 */
+use std::cmp::min;
+use std::iter::{Skip, Take};
+use std::ops::Range;
+
 /**
 <em>a</em>, <em>n</em>
 &nbsp;&rarr;&nbsp;
@@ -16,11 +20,74 @@ or distributed except according to those terms.
 pub trait StrideIterator<E>: Iterator<Item=E> + Sized {
     /**
 Creates an iterator which yields every `n`th element of the input iterator, including the first.
+
+Elements are skipped using `Iterator::nth`, so sources with random access (such as slice iterators) skip in constant time.
+
+# Failure
+
+This method will panic if `n` is zero.
     */
     fn stride(self, n: usize) -> Stride<Self> {
+        self.stride_from(0, n)
+    }
+
+    /**
+Creates an iterator which yields every `n`th element of the input iterator, starting with the element at index `offset`.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::StrideIterator;
+# fn main() {
+let r: Vec<_> = (0..10).stride_from(1, 3).collect();
+assert_eq!(r, vec![1, 4, 7]);
+# }
+```
+
+# Failure
+
+This method will panic if `n` is zero.
+    */
+    fn stride_from(self, offset: usize, n: usize) -> Stride<Self> {
+        assert!(n != 0, "stride must be non-zero");
         Stride {
             iter: self,
-            stride: n
+            stride: n,
+            skip: offset,
+        }
+    }
+
+    /**
+Treats the input iterator as a matrix stored in row-major order with `width` columns, allowing individual rows, columns and blocks to be extracted.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::StrideIterator;
+# fn main() {
+let m = [1, 2, 3,
+         4, 5, 6,
+         7, 8, 9];
+let col: Vec<_> = m.iter().cloned().grid(3).column(1).collect();
+assert_eq!(col, vec![2, 5, 8]);
+let diag: Vec<_> = m.iter().cloned().grid(3).diagonal().collect();
+assert_eq!(diag, vec![1, 5, 9]);
+let block: Vec<_> = m.iter().cloned().grid(3).block(1..3, 0..2).collect();
+assert_eq!(block, vec![4, 5, 7, 8]);
+# }
+```
+
+# Failure
+
+This method will panic if `width` is zero.
+    */
+    fn grid(self, width: usize) -> Grid<Self> {
+        assert!(width != 0, "grid width must be non-zero");
+        Grid {
+            iter: self,
+            width,
         }
     }
 }
@@ -31,6 +98,7 @@ impl<It, E> StrideIterator<E> for It where It: Iterator<Item=E> {}
 pub struct Stride<It> {
     iter: It,
     stride: usize,
+    skip: usize,
 }
 
 impl<It> Stride<It> {
@@ -40,36 +108,203 @@ Unwraps the iterator, returning the underlying iterator.
     pub fn unwrap(self) -> It {
         self.iter
     }
+
+    /*
+Returns how many elements will be yielded if the underlying iterator has `len` elements left.
+    */
+    fn remaining(&self, len: usize) -> usize {
+        len.saturating_sub(self.skip).div_ceil(self.stride)
+    }
 }
 
 impl<It, E> Iterator for Stride<It> where It: Iterator<Item=E> {
     type Item = E;
 
     fn next(&mut self) -> Option<E> {
-        let v = match self.iter.next() {
-            Some(v) => v,
-            None => return None
+        let skip = self.skip;
+        self.skip = self.stride - 1;
+        self.iter.nth(skip)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lb, ub) = self.iter.size_hint();
+        (self.remaining(lb), ub.map(|ub| self.remaining(ub)))
+    }
+}
+
+impl<It, E> DoubleEndedIterator for Stride<It> where It: DoubleEndedIterator<Item=E> + ExactSizeIterator {
+    fn next_back(&mut self) -> Option<E> {
+        // Skip the elements after the last one the front would reach.
+        let len = self.iter.len();
+        let n = self.remaining(len);
+        if n == 0 {
+            return None;
+        }
+        let last = self.skip + (n - 1) * self.stride;
+        self.iter.nth_back(len - 1 - last)
+    }
+}
+
+impl<It, E> ExactSizeIterator for Stride<It> where It: ExactSizeIterator<Item=E> {}
+
+/**
+A sequence viewed as a matrix stored in row-major order.
+
+See `StrideIterator::grid`.  Each view consumes the grid; clone the underlying iterator (or re-borrow the slice) to extract more than one.
+*/
+#[derive(Clone, Debug)]
+pub struct Grid<It> {
+    iter: It,
+    width: usize,
+}
+
+impl<It, E> Grid<It> where It: Iterator<Item=E> {
+    /**
+Returns the number of columns.
+    */
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /**
+Returns an iterator over row `r`.
+    */
+    pub fn row(self, r: usize) -> Take<Skip<It>> {
+        self.iter.skip(r * self.width).take(self.width)
+    }
+
+    /**
+Returns an iterator over column `c`.
+
+# Failure
+
+This method will panic if `c` is not less than the width.
+    */
+    pub fn column(self, c: usize) -> Stride<It> {
+        assert!(c < self.width, "column {} out of range for grid of width {}", c, self.width);
+        self.iter.stride_from(c, self.width)
+    }
+
+    /**
+Returns an iterator over the leading diagonal, starting at the top-left corner and ending at whichever of the last row or column is reached first.
+    */
+    pub fn diagonal(self) -> Take<Stride<It>> {
+        let width = self.width;
+        self.iter.stride(width + 1).take(width)
+    }
+
+    /**
+Returns an iterator over the rows `rows` and columns `cols` of the matrix, in row-major order.
+
+# Failure
+
+This method will panic if `cols` extends past the width.
+    */
+    pub fn block(self, rows: Range<usize>, cols: Range<usize>) -> Block<It> {
+        assert!(cols.end <= self.width, "columns {:?} out of range for grid of width {}", cols, self.width);
+        let block_width = cols.end.saturating_sub(cols.start);
+        let height = match block_width {
+            0 => 0,
+            _ => rows.end.saturating_sub(rows.start)
         };
+        Block {
+            iter: self.iter,
+            width: self.width,
+            block_width,
+            skip: rows.start * self.width + cols.start,
+            col: 0,
+            rows: height,
+        }
+    }
 
-        for _ in 0..(self.stride - 1) {
-            match self.iter.next() {
-                None => break,
-                _ => ()
-            }
+    /**
+Unwraps the grid, returning the underlying iterator.
+    */
+    pub fn unwrap(self) -> It {
+        self.iter
+    }
+}
+
+/**
+An iterator over a rectangular block of a matrix stored in row-major order.
+
+See `Grid::block`.
+*/
+#[derive(Clone, Debug)]
+pub struct Block<It> {
+    iter: It,
+    width: usize,
+    block_width: usize,
+    /// Elements to skip before the next element of the block.
+    skip: usize,
+    /// Column of the next element, relative to the block.
+    col: usize,
+    /// Rows left, including the current one.
+    rows: usize,
+}
+
+impl<It> Block<It> {
+    /**
+Unwraps the iterator, returning the underlying iterator.
+    */
+    pub fn unwrap(self) -> It {
+        self.iter
+    }
+
+    /*
+Returns how many elements will be yielded if the underlying iterator has `len` elements left.
+    */
+    fn remaining(&self, len: usize) -> usize {
+        if self.rows == 0 {
+            return 0;
+        }
+        let first_row = min(self.block_width - self.col, len.saturating_sub(self.skip));
+        let next_row = self.skip + self.width - self.col;
+        if len <= next_row {
+            return first_row;
         }
+        let rem = len - next_row;
+        let full = rem / self.width;
+        let more_rows = self.rows - 1;
+        match full < more_rows {
+            true => first_row + full * self.block_width + min(rem % self.width, self.block_width),
+            false => first_row + more_rows * self.block_width
+        }
+    }
+}
+
+impl<It, E> Iterator for Block<It> where It: Iterator<Item=E> {
+    type Item = E;
 
-        Some(v)
+    fn next(&mut self) -> Option<E> {
+        if self.rows == 0 {
+            return None;
+        }
+        let skip = self.skip;
+        self.col += 1;
+        self.skip = 0;
+        if self.col == self.block_width {
+            self.col = 0;
+            self.rows -= 1;
+            self.skip = self.width - self.block_width;
+        }
+        match self.iter.nth(skip) {
+            Some(e) => Some(e),
+            None => {
+                self.rows = 0;
+                None
+            }
+        }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        match self.iter.size_hint() {
-            (lb, Some(ub)) => ((lb + self.stride - 1) / self.stride,
-                                Some((ub + self.stride - 1) / self.stride)),
-            (lb, None) => (lb, None)
-        }
+        let (lb, ub) = self.iter.size_hint();
+        (self.remaining(lb), ub.map(|ub| self.remaining(ub)))
     }
 }
 
+impl<It, E> ExactSizeIterator for Block<It> where It: ExactSizeIterator<Item=E> {}
+
 #[test]
 fn test_stride() {
     use super::CloneEachIterator;
@@ -104,3 +339,125 @@ fn test_stride() {
     let it = v.iter().clone_each().stride(3);
     assert_eq!(it.size_hint(), (4, Some(4)));
 }
+
+#[test]
+fn test_stride_back() {
+    for len in 0..12 {
+        for n in 1..5 {
+            let v: Vec<usize> = (0..len).collect();
+            let expected: Vec<_> = v.iter().cloned().step_by(n).collect();
+
+            let it = v.iter().cloned().stride(n);
+            assert_eq!(it.len(), expected.len());
+            let r: Vec<_> = it.rev().collect();
+            assert_eq!(r, expected.iter().cloned().rev().collect::<Vec<_>>());
+
+            // Alternate ends.
+            let mut it = v.iter().cloned().stride(n);
+            let mut front = vec![];
+            let mut back = vec![];
+            while let Some(e) = it.next() {
+                front.push(e);
+                assert_eq!(it.len(), expected.len() - front.len() - back.len());
+                match it.next_back() {
+                    Some(e) => back.push(e),
+                    None => break
+                }
+            }
+            front.extend(back.into_iter().rev());
+            assert_eq!(front, expected);
+        }
+    }
+}
+
+#[test]
+fn test_stride_skips_with_nth() {
+    // `Range::nth` is constant time, so this would take forever if `Stride` skipped elements one at a time.
+    let n = usize::MAX / 4 + 1;
+    let mut it = (0..usize::MAX).stride(n);
+    assert_eq!(it.len(), 4);
+    assert_eq!(it.next(), Some(0));
+    assert_eq!(it.next(), Some(n));
+    assert_eq!(it.next_back(), Some(3 * n));
+    assert_eq!(it.next_back(), Some(2 * n));
+    assert_eq!(it.next_back(), None);
+}
+
+#[test]
+#[should_panic(expected = "stride must be non-zero")]
+fn test_stride_zero() {
+    let _ = (0..10).stride(0);
+}
+
+#[test]
+fn test_stride_from() {
+    for len in 0..12 {
+        for n in 1..5 {
+            for offset in 0..6 {
+                let v: Vec<usize> = (0..len).collect();
+                let expected: Vec<_> = v.iter().cloned().skip(offset).step_by(n).collect();
+                let it = v.iter().cloned().stride_from(offset, n);
+                assert_eq!(it.len(), expected.len());
+                assert_eq!(it.clone().collect::<Vec<_>>(), expected);
+                assert_eq!(it.rev().collect::<Vec<_>>(), expected.iter().cloned().rev().collect::<Vec<_>>());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_grid() {
+    // A 4x3 matrix, with the last row incomplete.
+    let m: Vec<usize> = (0..11).collect();
+    let grid = || m.iter().cloned().grid(3);
+
+    assert_eq!(grid().width(), 3);
+    assert_eq!(grid().row(1).collect::<Vec<_>>(), vec![3, 4, 5]);
+    assert_eq!(grid().row(3).len(), 2);
+    assert_eq!(grid().row(4).len(), 0);
+    assert_eq!(grid().column(2).collect::<Vec<_>>(), vec![2, 5, 8]);
+    assert_eq!(grid().column(1).rev().collect::<Vec<_>>(), vec![10, 7, 4, 1]);
+    assert_eq!(grid().diagonal().collect::<Vec<_>>(), vec![0, 4, 8]);
+    assert_eq!(m.iter().cloned().take(5).grid(3).diagonal().len(), 2);
+
+    let mut it = grid().block(1..4, 1..3);
+    assert_eq!(it.len(), 5);
+    assert_eq!(it.next(), Some(4));
+    assert_eq!(it.len(), 4);
+    assert_eq!(it.collect::<Vec<_>>(), vec![5, 7, 8, 10]);
+    assert_eq!(grid().block(2..2, 0..3).len(), 0);
+    assert_eq!(grid().block(0..4, 1..1).len(), 0);
+    assert_eq!(grid().block(5..9, 0..3).collect::<Vec<_>>(), vec![]);
+}
+
+#[test]
+fn test_block_len() {
+    for len in 0..20 {
+        for width in 1..5 {
+            for c0 in 0..width + 1 {
+                for c1 in c0..width + 1 {
+                    let v: Vec<usize> = (0..len).collect();
+                    let expected: Vec<_> = v.iter().cloned()
+                        .filter(|&e| e / width >= 1 && e / width < 4 && e % width >= c0 && e % width < c1)
+                        .collect();
+                    let mut it = v.iter().cloned().grid(width).block(1..4, c0..c1);
+                    let mut r = vec![];
+                    loop {
+                        assert_eq!(it.len(), expected.len() - r.len());
+                        match it.next() {
+                            Some(e) => r.push(e),
+                            None => break
+                        }
+                    }
+                    assert_eq!(r, expected);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "column 3 out of range")]
+fn test_grid_column_range() {
+    let _ = (0..10).grid(3).column(3);
+}
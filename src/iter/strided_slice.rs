@@ -0,0 +1,607 @@
+/*
+Copyright ⓒ 2015 grabbag contributors.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+/*!
+Strided views into slices.
+
+Unlike `StrideIterator::stride`, these borrow the underlying slice rather than consuming an iterator, so they can be indexed, split and (for `StridedSliceMut`) written through.
+
+# Example
+
+Halving the volume of the right channel of interleaved stereo samples, in place:
+
+```
+# extern crate grabbag;
+# use grabbag::iter::strided_slice::StridedSliceMut;
+# fn main() {
+let mut samples = [10, 20, 30, 40, 50, 60];
+{
+    let mut right = StridedSliceMut::new(&mut samples, 1, 2, 3);
+    for s in right.iter_mut() {
+        *s /= 2;
+    }
+    right[0] = 0;
+}
+assert_eq!(samples, [10, 0, 30, 20, 50, 30]);
+# }
+```
+*/
+use std::cmp::min;
+use std::fmt;
+use std::mem;
+use std::ops::{Index, IndexMut};
+
+/*
+Returns the part of `len` elements of a slice which a strided view covers: from the first element to the last, inclusive.  Panics if the view does not fit.
+*/
+fn covered(slice_len: usize, start: usize, stride: usize, len: usize) -> (usize, usize) {
+    assert!(stride != 0, "stride must be non-zero");
+    if len == 0 {
+        return (0, 0);
+    }
+    let end = (len - 1).checked_mul(stride)
+        .and_then(|n| n.checked_add(start))
+        .and_then(|n| n.checked_add(1));
+    match end {
+        Some(end) if end <= slice_len => (start, end),
+        _ => panic!("strided view of {} elements from {} by {} out of range for slice of length {}",
+            len, start, stride, slice_len)
+    }
+}
+
+/*
+Returns the range of the underlying slice covered by a view of `len` elements of a view, starting at index `start` and stepping by `stride`, along with the stride of the new view over the slice.  `view_stride` is the stride of the outer view.
+
+The combined stride can only overflow if the new view has at most one element, in which case the stride makes no difference, so it saturates.
+*/
+fn sub_range(view_len: usize, view_stride: usize, start: usize, stride: usize, len: usize) -> (usize, usize, usize) {
+    let (start, end) = covered(view_len, start, stride, len);
+    let stride = stride.saturating_mul(view_stride);
+    match len {
+        0 => (0, 0, stride),
+        _ => (start * view_stride, (end - 1) * view_stride + 1, stride)
+    }
+}
+
+/*
+Returns the number of elements in a strided view over a slice which starts with the first element and ends with the last.
+*/
+fn view_len(slice_len: usize, stride: usize) -> usize {
+    match slice_len {
+        0 => 0,
+        n => (n - 1) / stride + 1
+    }
+}
+
+/**
+A shared view of every `stride`th element of a slice.
+*/
+pub struct StridedSlice<'a, T: 'a> {
+    // Invariant: `slice` starts at the first element of the view, and ends at the last.
+    slice: &'a [T],
+    stride: usize,
+}
+
+impl<'a, T> StridedSlice<'a, T> {
+    /**
+Creates a view of `len` elements of `slice`, starting at index `start` and stepping by `stride`.
+
+# Failure
+
+This method will panic if `stride` is zero, or if the view extends past the end of `slice`.
+    */
+    pub fn new(slice: &'a [T], start: usize, stride: usize, len: usize) -> StridedSlice<'a, T> {
+        let (start, end) = covered(slice.len(), start, stride, len);
+        StridedSlice {
+            slice: &slice[start..end],
+            stride,
+        }
+    }
+
+    /**
+Returns the number of elements in the view.
+    */
+    pub fn len(&self) -> usize {
+        view_len(self.slice.len(), self.stride)
+    }
+
+    /**
+Returns `true` if the view has no elements.
+    */
+    pub fn is_empty(&self) -> bool {
+        self.slice.is_empty()
+    }
+
+    /**
+Returns the distance, in elements of the underlying slice, between consecutive elements of the view.
+    */
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /**
+Returns the `i`th element of the view, or `None` if it is out of range.
+    */
+    pub fn get(&self, i: usize) -> Option<&'a T> {
+        match i < self.len() {
+            true => Some(&self.slice[i * self.stride]),
+            false => None
+        }
+    }
+
+    /**
+Returns an iterator over the elements of the view.
+    */
+    pub fn iter(&self) -> Iter<'a, T> {
+        Iter {
+            slice: self.slice,
+            stride: self.stride,
+        }
+    }
+
+    /**
+Divides the view into two at index `mid`.  The first contains elements `[0, mid)`, the second `[mid, len)`.
+
+# Failure
+
+This method will panic if `mid > len`.
+    */
+    pub fn split_at(&self, mid: usize) -> (StridedSlice<'a, T>, StridedSlice<'a, T>) {
+        assert!(mid <= self.len(), "split index {} out of range for view of length {}", mid, self.len());
+        let (left, right) = self.slice.split_at(min(mid.saturating_mul(self.stride), self.slice.len()));
+        let left = match mid {
+            0 => &left[..0],
+            _ => &left[..(mid - 1) * self.stride + 1]
+        };
+        (StridedSlice { slice: left, stride: self.stride }, StridedSlice { slice: right, stride: self.stride })
+    }
+
+    /**
+Creates a view of `len` elements of this view, starting at index `start` and stepping by `stride`.
+
+# Failure
+
+This method will panic if `stride` is zero, or if the new view extends past the end of this one.
+    */
+    pub fn substride(&self, start: usize, stride: usize, len: usize) -> StridedSlice<'a, T> {
+        let (start, end, stride) = sub_range(self.len(), self.stride, start, stride, len);
+        StridedSlice {
+            slice: &self.slice[start..end],
+            stride,
+        }
+    }
+}
+
+impl<'a, T> Clone for StridedSlice<'a, T> {
+    fn clone(&self) -> StridedSlice<'a, T> {
+        *self
+    }
+}
+
+impl<'a, T> Copy for StridedSlice<'a, T> {}
+
+impl<'a, T> fmt::Debug for StridedSlice<'a, T> where T: fmt::Debug {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T> Index<usize> for StridedSlice<'a, T> {
+    type Output = T;
+
+    fn index(&self, i: usize) -> &T {
+        match self.get(i) {
+            Some(e) => e,
+            None => panic!("index {} out of range for view of length {}", i, self.len())
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for StridedSlice<'a, T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/**
+A mutable view of every `stride`th element of a slice.
+*/
+pub struct StridedSliceMut<'a, T: 'a> {
+    // Invariant: `slice` starts at the first element of the view, and ends at the last.
+    slice: &'a mut [T],
+    stride: usize,
+}
+
+impl<'a, T> StridedSliceMut<'a, T> {
+    /**
+Creates a view of `len` elements of `slice`, starting at index `start` and stepping by `stride`.
+
+# Failure
+
+This method will panic if `stride` is zero, or if the view extends past the end of `slice`.
+    */
+    pub fn new(slice: &'a mut [T], start: usize, stride: usize, len: usize) -> StridedSliceMut<'a, T> {
+        let (start, end) = covered(slice.len(), start, stride, len);
+        StridedSliceMut {
+            slice: &mut slice[start..end],
+            stride,
+        }
+    }
+
+    /**
+Returns the number of elements in the view.
+    */
+    pub fn len(&self) -> usize {
+        view_len(self.slice.len(), self.stride)
+    }
+
+    /**
+Returns `true` if the view has no elements.
+    */
+    pub fn is_empty(&self) -> bool {
+        self.slice.is_empty()
+    }
+
+    /**
+Returns the distance, in elements of the underlying slice, between consecutive elements of the view.
+    */
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /**
+Returns a shared view of the same elements.
+    */
+    pub fn as_strided_slice(&self) -> StridedSlice<'_, T> {
+        StridedSlice {
+            slice: self.slice,
+            stride: self.stride,
+        }
+    }
+
+    /**
+Returns the `i`th element of the view, or `None` if it is out of range.
+    */
+    pub fn get(&self, i: usize) -> Option<&T> {
+        self.as_strided_slice().get(i)
+    }
+
+    /**
+Returns the `i`th element of the view mutably, or `None` if it is out of range.
+    */
+    pub fn get_mut(&mut self, i: usize) -> Option<&mut T> {
+        match i < self.len() {
+            true => Some(&mut self.slice[i * self.stride]),
+            false => None
+        }
+    }
+
+    /**
+Returns an iterator over the elements of the view.
+    */
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.as_strided_slice().iter()
+    }
+
+    /**
+Returns an iterator which allows modifying the elements of the view.
+    */
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            slice: &mut *self.slice,
+            stride: self.stride,
+        }
+    }
+
+    /**
+Divides the view into two mutable views at index `mid`.  The first contains elements `[0, mid)`, the second `[mid, len)`.
+
+# Failure
+
+This method will panic if `mid > len`.
+    */
+    pub fn split_at_mut(&mut self, mid: usize) -> (StridedSliceMut<'_, T>, StridedSliceMut<'_, T>) {
+        StridedSliceMut { slice: &mut *self.slice, stride: self.stride }.into_split_at(mid)
+    }
+
+    /**
+Divides the view into two mutable views at index `mid`, consuming it.
+
+# Failure
+
+This method will panic if `mid > len`.
+    */
+    pub fn into_split_at(self, mid: usize) -> (StridedSliceMut<'a, T>, StridedSliceMut<'a, T>) {
+        let len = self.len();
+        assert!(mid <= len, "split index {} out of range for view of length {}", mid, len);
+        let stride = self.stride;
+        let at = min(mid.saturating_mul(stride), self.slice.len());
+        let (left, right) = self.slice.split_at_mut(at);
+        let left = match mid {
+            0 => &mut left[..0],
+            _ => &mut left[..(mid - 1) * stride + 1]
+        };
+        (StridedSliceMut { slice: left, stride }, StridedSliceMut { slice: right, stride })
+    }
+
+    /**
+Creates a mutable view of `len` elements of this view, starting at index `start` and stepping by `stride`.
+
+# Failure
+
+This method will panic if `stride` is zero, or if the new view extends past the end of this one.
+    */
+    pub fn substride_mut(&mut self, start: usize, stride: usize, len: usize) -> StridedSliceMut<'_, T> {
+        let (start, end, stride) = sub_range(self.len(), self.stride, start, stride, len);
+        StridedSliceMut {
+            slice: &mut self.slice[start..end],
+            stride,
+        }
+    }
+}
+
+impl<'a, T> fmt::Debug for StridedSliceMut<'a, T> where T: fmt::Debug {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        self.as_strided_slice().fmt(fmt)
+    }
+}
+
+impl<'a, T> Index<usize> for StridedSliceMut<'a, T> {
+    type Output = T;
+
+    fn index(&self, i: usize) -> &T {
+        match self.get(i) {
+            Some(e) => e,
+            None => panic!("index {} out of range for view of length {}", i, self.len())
+        }
+    }
+}
+
+impl<'a, T> IndexMut<usize> for StridedSliceMut<'a, T> {
+    fn index_mut(&mut self, i: usize) -> &mut T {
+        let len = self.len();
+        match self.get_mut(i) {
+            Some(e) => e,
+            None => panic!("index {} out of range for view of length {}", i, len)
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for StridedSliceMut<'a, T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        IterMut {
+            slice: self.slice,
+            stride: self.stride,
+        }
+    }
+}
+
+/**
+An iterator over the elements of a `StridedSlice`.
+*/
+pub struct Iter<'a, T: 'a> {
+    // Same invariant as `StridedSlice`.
+    slice: &'a [T],
+    stride: usize,
+}
+
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Iter<'a, T> {
+        Iter {
+            slice: self.slice,
+            stride: self.stride,
+        }
+    }
+}
+
+impl<'a, T> fmt::Debug for Iter<'a, T> where T: fmt::Debug {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("Iter").field(&StridedSlice { slice: self.slice, stride: self.stride }).finish()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let (first, rest) = self.slice.split_first()?;
+        self.slice = &rest[min(self.stride - 1, rest.len())..];
+        Some(first)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = view_len(self.slice.len(), self.stride);
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        let len = view_len(self.slice.len(), self.stride);
+        if len == 0 {
+            return None;
+        }
+        let (rest, last) = self.slice.split_at((len - 1) * self.stride);
+        self.slice = &rest[..rest.len().saturating_sub(self.stride - 1)];
+        Some(&last[0])
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+/**
+An iterator over the elements of a `StridedSliceMut`, allowing them to be modified.
+*/
+pub struct IterMut<'a, T: 'a> {
+    // Same invariant as `StridedSliceMut`.
+    slice: &'a mut [T],
+    stride: usize,
+}
+
+impl<'a, T> fmt::Debug for IterMut<'a, T> where T: fmt::Debug {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("IterMut").field(&StridedSlice { slice: &*self.slice, stride: self.stride }).finish()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        let slice = mem::take(&mut self.slice);
+        let (first, rest) = slice.split_first_mut()?;
+        let skip = min(self.stride - 1, rest.len());
+        self.slice = &mut rest[skip..];
+        Some(first)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = view_len(self.slice.len(), self.stride);
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        let len = view_len(self.slice.len(), self.stride);
+        if len == 0 {
+            return None;
+        }
+        let slice = mem::take(&mut self.slice);
+        let (rest, last) = slice.split_at_mut((len - 1) * self.stride);
+        let keep = rest.len().saturating_sub(self.stride - 1);
+        self.slice = &mut rest[..keep];
+        Some(&mut last[0])
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+#[test]
+fn test_strided_slice() {
+    let v: Vec<usize> = (0..10).collect();
+
+    let s = StridedSlice::new(&v, 1, 3, 3);
+    assert_eq!(s.len(), 3);
+    assert_eq!(s.stride(), 3);
+    assert_eq!((s[0], s[1], s[2]), (1, 4, 7));
+    assert_eq!(s.get(3), None);
+    assert_eq!(s.iter().cloned().collect::<Vec<_>>(), vec![1, 4, 7]);
+    assert_eq!(s.iter().rev().cloned().collect::<Vec<_>>(), vec![7, 4, 1]);
+    assert_eq!(format!("{:?}", s), "[1, 4, 7]");
+
+    let (a, b) = s.split_at(1);
+    assert_eq!(a.iter().cloned().collect::<Vec<_>>(), vec![1]);
+    assert_eq!(b.iter().cloned().collect::<Vec<_>>(), vec![4, 7]);
+    let (a, b) = s.split_at(0);
+    assert!(a.is_empty());
+    assert_eq!(b.len(), 3);
+    let (a, b) = s.split_at(3);
+    assert_eq!(a.len(), 3);
+    assert!(b.is_empty());
+
+    let s = StridedSlice::new(&v, 0, 1, 10);
+    let t = s.substride(1, 2, 4);
+    assert_eq!(t.iter().cloned().collect::<Vec<_>>(), vec![1, 3, 5, 7]);
+    let u = t.substride(1, 2, 2);
+    assert_eq!(u.iter().cloned().collect::<Vec<_>>(), vec![3, 7]);
+    assert!(t.substride(4, 1, 0).is_empty());
+
+    assert!(StridedSlice::new(&v, 10, 5, 0).is_empty());
+    assert_eq!(StridedSlice::new(&v, 9, 5, 1).iter().cloned().collect::<Vec<_>>(), vec![9]);
+}
+
+#[test]
+fn test_strided_iter_ends() {
+    let v: Vec<usize> = (0..20).collect();
+    for stride in 1..5 {
+        for len in 0..(20 / stride) {
+            let s = StridedSlice::new(&v, 1, stride, len);
+            let expected: Vec<_> = (0..len).map(|i| 1 + i * stride).collect();
+            let mut it = s.iter();
+            let mut front = vec![];
+            let mut back = vec![];
+            loop {
+                assert_eq!(it.len(), len - front.len() - back.len());
+                match it.next() {
+                    Some(&e) => front.push(e),
+                    None => break
+                }
+                match it.next_back() {
+                    Some(&e) => back.push(e),
+                    None => break
+                }
+            }
+            front.extend(back.into_iter().rev());
+            assert_eq!(front, expected);
+        }
+    }
+}
+
+#[test]
+fn test_strided_slice_mut() {
+    let mut v: Vec<usize> = (0..10).collect();
+    {
+        let mut s = StridedSliceMut::new(&mut v, 0, 2, 5);
+        s[1] = 100;
+        for e in s.iter_mut().rev().take(2) {
+            *e += 1000;
+        }
+        {
+            let (mut a, mut b) = s.split_at_mut(2);
+            a[0] = 50;
+            b[0] = 60;
+        }
+        {
+            let mut t = s.substride_mut(1, 2, 2);
+            t[1] = 70;
+        }
+        assert_eq!(format!("{:?}", s), "[50, 100, 60, 70, 1008]");
+        for e in s {
+            *e += 1;
+        }
+    }
+    assert_eq!(v, vec![51, 1, 101, 3, 61, 5, 71, 7, 1009, 9]);
+}
+
+#[test]
+fn test_strided_slice_huge_stride() {
+    // A single element view may have any stride, and nesting must not overflow.
+    let v: Vec<_> = (0..10).collect();
+    let s = StridedSlice::new(&v, 1, 3, 3);
+    let t = s.substride(2, usize::MAX, 1);
+    assert_eq!(t.iter().cloned().collect::<Vec<_>>(), vec![7]);
+    let (l, r) = t.split_at(1);
+    assert_eq!((l.len(), r.len()), (1, 0));
+    let u = t.substride(0, usize::MAX, 1);
+    assert_eq!(u.iter().cloned().collect::<Vec<_>>(), vec![7]);
+
+    let mut v = v;
+    let mut s = StridedSliceMut::new(&mut v, 0, usize::MAX / 2, 1);
+    {
+        let mut t = s.substride_mut(0, 3, 1);
+        t[0] = 100;
+        let (l, r) = t.split_at_mut(1);
+        assert_eq!((l.len(), r.len()), (1, 0));
+    }
+    assert_eq!(s[0], 100);
+}
+
+#[test]
+#[should_panic(expected = "out of range")]
+fn test_strided_slice_bounds() {
+    let v = [0; 10];
+    let _ = StridedSlice::new(&v, 1, 3, 4);
+}
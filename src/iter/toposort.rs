@@ -0,0 +1,315 @@
+/*
+Copyright ⓒ 2015 grabbag contributors.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::error::Error;
+use std::fmt;
+
+/**
+Topological sorting of dependency graphs, given as a sequence of `(node, dependency)` edges.
+
+Every node is ordered after all of its dependencies.  Nodes only exist by virtue of appearing in an edge.  Where more than one node is ready at the same time, the smallest (according to `Ord`) comes first, so the result is deterministic.
+*/
+pub trait ToposortIterator<N>: Iterator<Item=(N, N)> + Sized where N: Ord + Clone {
+    /**
+Returns the nodes of the graph in dependency order, or the nodes of a cycle if there is no such order.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::ToposortIterator;
+# fn main() {
+let edges = vec![("app", "lib"), ("lib", "core"), ("app", "core"), ("test", "lib")];
+assert_eq!(edges.into_iter().toposort(), Ok(vec!["core", "lib", "app", "test"]));
+
+let edges = vec![("a", "b"), ("b", "c"), ("c", "a"), ("d", "a")];
+assert_eq!(edges.into_iter().toposort().unwrap_err().nodes, vec!["a", "b", "c"]);
+# }
+```
+    */
+    fn toposort(self) -> Result<Vec<N>, Cycle<N>> {
+        self.toposort_lazy().collect()
+    }
+
+    /**
+Returns an iterator over the nodes of the graph in dependency order, using Kahn's algorithm.
+
+The edges are consumed up front, but the order itself is computed as the iterator is advanced.  If the graph contains a cycle, the iterator yields every node that does not depend on the cycle, followed by a single `Err`.
+    */
+    fn toposort_lazy(self) -> Toposort<N> {
+        Toposort::new(self)
+    }
+
+    /**
+Returns an iterator over the nodes of the graph grouped into layers.
+
+Each layer contains, in ascending order, the nodes whose dependencies are all in earlier layers.  The nodes within a layer do not depend on one another, and so can be processed in parallel.  If the graph contains a cycle, the final item is an `Err`.
+
+# Example
+
+```
+# extern crate grabbag;
+# use grabbag::iter::ToposortIterator;
+# fn main() {
+let edges = vec![(3, 1), (3, 2), (4, 3), (5, 1)];
+let r: Result<Vec<_>, _> = edges.into_iter().toposort_layers().collect();
+assert_eq!(r, Ok(vec![vec![1, 2], vec![3, 5], vec![4]]));
+# }
+```
+    */
+    fn toposort_layers(self) -> ToposortLayers<N> {
+        ToposortLayers {
+            graph: Graph::new(self),
+        }
+    }
+}
+
+impl<It, N> ToposortIterator<N> for It where It: Iterator<Item=(N, N)>, N: Ord + Clone {}
+
+/**
+The error produced when a graph contains a dependency cycle.
+*/
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Cycle<N> {
+    /**
+The nodes on the cycle.  Each node depends on the next, and the last depends on the first.
+    */
+    pub nodes: Vec<N>,
+}
+
+impl<N> fmt::Display for Cycle<N> where N: fmt::Debug {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "dependency cycle between {:?}", self.nodes)
+    }
+}
+
+impl<N> Error for Cycle<N> where N: fmt::Debug {}
+
+/*
+The state of Kahn's algorithm.  Nodes are numbered in ascending order, so comparing numbers is equivalent to comparing nodes.
+*/
+#[derive(Clone, Debug)]
+struct Graph<N> {
+    nodes: Vec<Option<N>>,
+    dependents: Vec<Vec<usize>>,
+    dependencies: Vec<Vec<usize>>,
+    in_degree: Vec<usize>,
+    ready: BinaryHeap<Reverse<usize>>,
+    remaining: usize,
+    failed: bool,
+}
+
+impl<N> Graph<N> where N: Ord + Clone {
+    fn new<It>(edges: It) -> Graph<N> where It: Iterator<Item=(N, N)> {
+        let edges = edges.collect::<Vec<_>>();
+
+        let mut ids = BTreeMap::new();
+        for (node, dep) in &edges {
+            ids.entry(node.clone()).or_insert(0);
+            ids.entry(dep.clone()).or_insert(0);
+        }
+        for (i, id) in ids.values_mut().enumerate() {
+            *id = i;
+        }
+
+        let n = ids.len();
+        let mut dependents = vec![vec![]; n];
+        let mut dependencies = vec![vec![]; n];
+        let mut in_degree = vec![0; n];
+        for (node, dep) in edges {
+            let (node, dep) = (ids[&node], ids[&dep]);
+            dependents[dep].push(node);
+            dependencies[node].push(dep);
+            in_degree[node] += 1;
+        }
+        for deps in &mut dependencies {
+            deps.sort();
+            deps.dedup();
+        }
+
+        let ready = (0..n).filter(|&i| in_degree[i] == 0).map(Reverse).collect();
+
+        Graph {
+            nodes: ids.into_keys().map(Some).collect(),
+            dependents,
+            dependencies,
+            in_degree,
+            ready,
+            remaining: n,
+            failed: false,
+        }
+    }
+}
+
+impl<N> Graph<N> {
+    /*
+Removes a node from the graph, making any dependents with no other outstanding dependencies ready.
+    */
+    fn release(&mut self, i: usize) -> N {
+        for &d in &self.dependents[i] {
+            self.in_degree[d] -= 1;
+            if self.in_degree[d] == 0 {
+                self.ready.push(Reverse(d));
+            }
+        }
+        self.remaining -= 1;
+        self.nodes[i].take().expect("node released twice")
+    }
+
+    /*
+Returns the error for a stalled graph, if there is one.  Once nothing is ready, every remaining node has a remaining dependency, so following the smallest such dependency from the smallest remaining node must eventually revisit a node.
+    */
+    fn stall(&mut self) -> Option<Cycle<N>> where N: Clone {
+        if self.remaining == 0 || self.failed {
+            return None;
+        }
+        self.failed = true;
+
+        let start = (0..self.nodes.len())
+            .find(|&i| self.nodes[i].is_some())
+            .expect("remaining node");
+        let mut on_path = vec![None; self.nodes.len()];
+        let mut path = vec![];
+        let mut cur = start;
+        while on_path[cur].is_none() {
+            on_path[cur] = Some(path.len());
+            path.push(cur);
+            cur = *self.dependencies[cur].iter()
+                .find(|&&d| self.nodes[d].is_some())
+                .expect("remaining dependency");
+        }
+
+        let first = on_path[cur].unwrap();
+        Some(Cycle {
+            nodes: path[first..].iter()
+                .map(|&i| self.nodes[i].clone().expect("remaining node"))
+                .collect(),
+        })
+    }
+
+    fn size_hint(&self, ready: usize) -> (usize, Option<usize>) {
+        match self.failed {
+            true => (0, Some(0)),
+            false => (ready, Some(self.remaining))
+        }
+    }
+}
+
+/**
+An iterator over the nodes of a graph in dependency order.
+
+See `ToposortIterator::toposort_lazy`.
+*/
+#[derive(Clone, Debug)]
+pub struct Toposort<N> {
+    graph: Graph<N>,
+}
+
+impl<N> Toposort<N> where N: Ord + Clone {
+    fn new<It>(edges: It) -> Toposort<N> where It: Iterator<Item=(N, N)> {
+        Toposort {
+            graph: Graph::new(edges),
+        }
+    }
+}
+
+impl<N> Iterator for Toposort<N> where N: Clone {
+    type Item = Result<N, Cycle<N>>;
+
+    fn next(&mut self) -> Option<Result<N, Cycle<N>>> {
+        match self.graph.ready.pop() {
+            Some(Reverse(i)) => Some(Ok(self.graph.release(i))),
+            None => self.graph.stall().map(Err)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.graph.size_hint(self.graph.ready.len())
+    }
+}
+
+/**
+An iterator over the nodes of a graph in dependency order, grouped into layers of mutually independent nodes.
+
+See `ToposortIterator::toposort_layers`.
+*/
+#[derive(Clone, Debug)]
+pub struct ToposortLayers<N> {
+    graph: Graph<N>,
+}
+
+impl<N> Iterator for ToposortLayers<N> where N: Clone {
+    type Item = Result<Vec<N>, Cycle<N>>;
+
+    fn next(&mut self) -> Option<Result<Vec<N>, Cycle<N>>> {
+        if self.graph.ready.is_empty() {
+            return self.graph.stall().map(Err);
+        }
+
+        // Collect the whole layer before releasing any of it, so that newly ready nodes go into the next layer.
+        let mut layer = Vec::with_capacity(self.graph.ready.len());
+        while let Some(Reverse(i)) = self.graph.ready.pop() {
+            layer.push(i);
+        }
+        Some(Ok(layer.into_iter().map(|i| self.graph.release(i)).collect()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let ready = match self.graph.ready.is_empty() {
+            true => 0,
+            false => 1
+        };
+        self.graph.size_hint(ready)
+    }
+}
+
+#[test]
+fn test_toposort() {
+    let edges: Vec<(u32, u32)> = vec![];
+    assert_eq!(edges.into_iter().toposort(), Ok(vec![]));
+
+    // Ties are broken by `Ord`, not input order.
+    let edges = vec![("z", "b"), ("y", "a"), ("x", "b")];
+    assert_eq!(edges.into_iter().toposort(), Ok(vec!["a", "b", "x", "y", "z"]));
+
+    // Duplicate edges are harmless.
+    let edges = vec![(2, 1), (2, 1), (3, 2), (3, 1)];
+    assert_eq!(edges.into_iter().toposort(), Ok(vec![1, 2, 3]));
+
+    let edges = vec![(1, 1)];
+    assert_eq!(edges.into_iter().toposort(), Err(Cycle { nodes: vec![1] }));
+}
+
+#[test]
+fn test_toposort_lazy() {
+    let edges = vec![(5, 4), (4, 3), (3, 4), (2, 1), (6, 2)];
+    let mut it = edges.into_iter().toposort_lazy();
+    assert_eq!(it.size_hint(), (1, Some(6)));
+    assert_eq!(it.next(), Some(Ok(1)));
+    assert_eq!(it.next(), Some(Ok(2)));
+    assert_eq!(it.next(), Some(Ok(6)));
+    let err = it.next().unwrap().unwrap_err();
+    assert_eq!(err.nodes, vec![3, 4]);
+    assert_eq!(err.to_string(), "dependency cycle between [3, 4]");
+    assert_eq!(it.size_hint(), (0, Some(0)));
+    assert_eq!(it.next(), None);
+}
+
+#[test]
+fn test_toposort_layers() {
+    let edges = vec![('d', 'b'), ('d', 'c'), ('b', 'a'), ('c', 'a'), ('e', 'a')];
+    let r: Vec<_> = edges.into_iter().toposort_layers().collect();
+    assert_eq!(r, vec![Ok(vec!['a']), Ok(vec!['b', 'c', 'e']), Ok(vec!['d'])]);
+
+    let edges = vec![(2, 1), (3, 2), (2, 3)];
+    let r: Vec<_> = edges.into_iter().toposort_layers().collect();
+    assert_eq!(r, vec![Ok(vec![1]), Err(Cycle { nodes: vec![2, 3] })]);
+}